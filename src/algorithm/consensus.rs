@@ -0,0 +1,149 @@
+use crate::{
+    algorithm::normalform::{dnf_terms, MAX_TERMS},
+    expression::Expression,
+    term::Term,
+};
+
+/// Every prime implicant of `exp`, sorted. Works on the terms of its DNF by iterated
+/// consensus, so no minterms get expanded.
+pub fn prime_implicants(exp: &Expression) -> Result<Vec<Term>, String> {
+    let mut primes = iterated_consensus(dnf_terms(exp, MAX_TERMS)?)?;
+    primes.sort();
+    Ok(primes)
+}
+
+/// Blake canonical form, the OR of all prime implicants
+pub fn blake(exp: &Expression) -> Result<Expression, String> {
+    Ok(Expression::new_from_terms(prime_implicants(exp)?))
+}
+
+/// Add consensus terms until there are none left that aren't absorbed, dropping every term
+/// absorbed along the way. What's left are exactly the prime implicants.
+pub fn iterated_consensus(terms: Vec<Term>) -> Result<Vec<Term>, String> {
+    // absorbed terms leave a hole, so the indices of the others stay put and every pair of live
+    // terms is compared once: when the later one comes up as `i`
+    let mut primes: Vec<Option<Term>> = vec![];
+    for term in terms {
+        insert(&mut primes, term);
+    }
+
+    let mut i = 0;
+    while i < primes.len() {
+        let mut j = 0;
+        while j < i {
+            let (Some(left), Some(right)) = (&primes[i], &primes[j]) else {
+                j += 1;
+                continue;
+            };
+            if let Some(consensus) = left.consensus(right) {
+                insert(&mut primes, consensus);
+                if primes.iter().flatten().count() > MAX_TERMS {
+                    return Err(format!(
+                        "There are more than {} prime implicants, that's too many to list!",
+                        MAX_TERMS
+                    ));
+                }
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    Ok(primes.into_iter().flatten().collect())
+}
+
+/// Adds `term` unless it is absorbed, and drops what it absorbs
+fn insert(primes: &mut Vec<Option<Term>>, term: Term) {
+    if primes.iter().flatten().any(|prime| prime.covers(&term)) {
+        return;
+    }
+    for slot in primes.iter_mut() {
+        if slot.as_ref().is_some_and(|prime| term.covers(prime)) {
+            *slot = None;
+        }
+    }
+    primes.push(Some(term));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algorithm::mintermmini::{expression_minterms, minimize},
+        constant::Constant,
+        parser::grammar::parse,
+        scope::VarScope,
+        testing::Generator,
+        traits::{Eval, VarVisibility},
+        var::VarName,
+    };
+
+    fn is_implicant(term: &Term, exp: &Expression, names: &[VarName]) -> bool {
+        (0..(1u64 << names.len())).all(|minterm| {
+            let scope = VarScope::new_from_minterm(names, minterm);
+            term.evaluate(&scope) != Ok(Constant::ONE) || exp.evaluate(&scope) == Ok(Constant::ONE)
+        })
+    }
+
+    #[test]
+    fn consensus_terms() {
+        assert_eq!(
+            blake(&parse("a b | ~a c").unwrap()).unwrap(),
+            parse("a b | ~a c | b c").unwrap()
+        );
+        assert_eq!(
+            blake(&parse("a | ~a").unwrap()).unwrap(),
+            parse("1").unwrap()
+        );
+        assert_eq!(blake(&parse("a ~a").unwrap()).unwrap(), parse("0").unwrap());
+    }
+
+    #[test]
+    fn many_variables() {
+        // 40 variables, far too many to go through minterms
+        let source = (0..20)
+            .map(|i| format!("x{} x{}", i, i + 20))
+            .collect::<Vec<String>>()
+            .join(" | ");
+        assert_eq!(
+            prime_implicants(&parse(&source).unwrap()).unwrap().len(),
+            20
+        );
+    }
+
+    #[test]
+    fn all_primes() {
+        let mut generator = Generator(0xb1a);
+        for _ in 0..100 {
+            let exp = generator.expression(3);
+            let names = exp.get_sorted_variables();
+            let primes = prime_implicants(&exp).unwrap();
+
+            for prime in primes.iter() {
+                assert!(is_implicant(prime, &exp, &names), "{} of {}", prime, exp);
+                for var in prime.vars() {
+                    let vars = prime.vars().iter().filter(|v| *v != var).copied();
+                    let shorter = Term::new_from_vars(vars.collect());
+                    assert!(
+                        !is_implicant(&shorter, &exp, &names),
+                        "{} of {}",
+                        prime,
+                        exp
+                    );
+                }
+            }
+
+            // none are missing, Quine–McCluskey finds every prime from the minterms
+            let (names, minterms) = expression_minterms(&exp).unwrap();
+            let terms: Vec<Term> = minterms
+                .iter()
+                .map(|minterm| Term::new_from_minterm_of(&names, *minterm))
+                .collect();
+            let mut expected = minimize(&terms);
+            expected.sort();
+            expected.dedup();
+            let mut found = primes.clone();
+            found.sort();
+            assert_eq!(found, expected, "{}", exp);
+        }
+    }
+}
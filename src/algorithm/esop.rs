@@ -0,0 +1,318 @@
+use crate::{
+    algorithm::{
+        mintermmini::minimal_terms,
+        reedmuller::{anf, best_polarity, xor_of_terms},
+    },
+    expression::Expression,
+    term::Term,
+    var::{Var, VarName},
+};
+
+/// Exclusive-or sum of products, the terms may overlap since every minterm covered an even
+/// number of times cancels out
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Esop {
+    pub names: Vec<VarName>,
+    pub terms: Vec<Term>,
+}
+
+impl Esop {
+    pub fn literal_count(&self) -> usize {
+        self.terms.iter().map(|term| term.vars().len()).sum()
+    }
+
+    pub fn to_expression(&self) -> Expression {
+        xor_of_terms(&self.terms)
+    }
+}
+
+/// Sizes of the ESOP next to the minimal sum of products of the same function
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EsopComparison {
+    pub esop_terms: usize,
+    pub esop_literals: usize,
+    pub sop_terms: usize,
+    pub sop_literals: usize,
+}
+
+impl std::fmt::Display for EsopComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "ESOP: {} terms, {} literals",
+            self.esop_terms, self.esop_literals
+        )?;
+        write!(
+            f,
+            "SOP:  {} terms, {} literals",
+            self.sop_terms, self.sop_literals
+        )
+    }
+}
+
+/// Small ESOP equivalent to `exp`. Starts from the best fixed polarity Reed–Muller form and
+/// improves it with EXORLINK cube transformations, it's a heuristic and not always minimal.
+/// With too many variables to search every polarity it starts from the ANF instead.
+pub fn minimize_esop(exp: &Expression) -> Result<Esop, String> {
+    let start = best_polarity(exp).or_else(|_| anf(exp))?;
+    let cubes = start
+        .terms
+        .iter()
+        .map(|term| Cube::from_term(&start.names, term))
+        .collect();
+
+    let terms = exorcise(cubes)
+        .iter()
+        .map(|cube| cube.to_term(&start.names))
+        .collect();
+    Ok(Esop {
+        names: start.names,
+        terms,
+    })
+}
+
+pub fn compare_with_sop(exp: &Expression, esop: &Esop) -> Result<EsopComparison, String> {
+    let sop = minimal_terms(exp)?;
+    Ok(EsopComparison {
+        esop_terms: esop.terms.len(),
+        esop_literals: esop.literal_count(),
+        sop_terms: sop.len(),
+        sop_literals: sop.iter().map(|term| term.vars().len()).sum(),
+    })
+}
+
+/// Product over the variables at the bits of `care`, positive where `value` is set. Bits are
+/// in the order of minterms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Cube {
+    care: u64,
+    value: u64,
+}
+
+impl Cube {
+    fn from_term(names: &[VarName], term: &Term) -> Self {
+        let mut cube = Cube { care: 0, value: 0 };
+        for var in term.vars() {
+            let i = names.iter().position(|name| *name == var.name()).unwrap();
+            let bit = 1 << (names.len() - 1 - i);
+            cube.care |= bit;
+            if !var.is_negated() {
+                cube.value |= bit;
+            }
+        }
+        cube
+    }
+
+    fn to_term(self, names: &[VarName]) -> Term {
+        let len = names.len();
+        let vars = (0..len)
+            .filter(|i| (self.care >> (len - 1 - i)) & 1 == 1)
+            .map(|i| Var::new_from_name(names[i], (self.value >> (len - 1 - i)) & 1 == 0))
+            .collect();
+        Term::new_from_vars(vars)
+    }
+
+    /// Bits where the literals differ, a missing variable counts as a third value
+    fn differences(self, other: Self) -> u64 {
+        (self.care ^ other.care) | (self.care & other.care & (self.value ^ other.value))
+    }
+
+    fn with_bit_of(self, bit: u64, other: Self) -> Self {
+        Cube {
+            care: (self.care & !bit) | (other.care & bit),
+            value: (self.value & !bit) | (other.value & bit),
+        }
+    }
+
+    /// x ^ ~x = 1, x ^ 1 = ~x and ~x ^ 1 = x, the bit gets the value neither cube has
+    fn with_third_value(self, bit: u64, other: Self) -> Self {
+        if self.care & other.care & bit != 0 {
+            Cube {
+                care: self.care & !bit,
+                value: self.value & !bit,
+            }
+        } else {
+            let cared = if self.care & bit != 0 { self } else { other };
+            Cube {
+                care: self.care | bit,
+                value: (self.value & !bit) | (!cared.value & bit),
+            }
+        }
+    }
+
+    fn literals(self) -> u32 {
+        self.care.count_ones()
+    }
+}
+
+fn cost(cubes: &[Cube]) -> (usize, u32) {
+    (cubes.len(), cubes.iter().map(|cube| cube.literals()).sum())
+}
+
+fn exorcise(mut cubes: Vec<Cube>) -> Vec<Cube> {
+    merge(&mut cubes);
+
+    'improve: loop {
+        for distance in 2..=3 {
+            for i in 0..cubes.len() {
+                for j in (i + 1)..cubes.len() {
+                    if cubes[i].differences(cubes[j]).count_ones() != distance {
+                        continue;
+                    }
+                    for linked in exorlink(cubes[i], cubes[j]) {
+                        let mut candidate: Vec<Cube> = cubes
+                            .iter()
+                            .enumerate()
+                            .filter(|(k, _)| *k != i && *k != j)
+                            .map(|(_, cube)| *cube)
+                            .chain(linked)
+                            .collect();
+                        merge(&mut candidate);
+                        // only strict improvements, so this can't go around in circles
+                        if cost(&candidate) < cost(&cubes) {
+                            cubes = candidate;
+                            continue 'improve;
+                        }
+                    }
+                }
+            }
+        }
+        return cubes;
+    }
+}
+
+/// Cancel equal cubes and join the ones differing in a single variable
+fn merge(cubes: &mut Vec<Cube>) {
+    'restart: loop {
+        for i in 0..cubes.len() {
+            for j in (i + 1)..cubes.len() {
+                let differences = cubes[i].differences(cubes[j]);
+                match differences.count_ones() {
+                    0 => {
+                        cubes.remove(j);
+                        cubes.remove(i);
+                    }
+                    1 => {
+                        cubes[i] = cubes[i].with_third_value(differences, cubes[j]);
+                        cubes.remove(j);
+                    }
+                    _ => continue,
+                }
+                continue 'restart;
+            }
+        }
+        return;
+    }
+}
+
+/// Every way of writing `left ^ right` as one cube per differing variable. For the order
+/// p₁..pₖ the i-th cube takes pⱼ from `right` for j < i, the third value at pᵢ and the rest
+/// from `left`.
+fn exorlink(left: Cube, right: Cube) -> Vec<Vec<Cube>> {
+    let differences = left.differences(right);
+    let bits: Vec<u64> = (0..64)
+        .map(|i| 1u64 << i)
+        .filter(|bit| differences & bit != 0)
+        .collect();
+
+    permutations(&bits)
+        .into_iter()
+        .map(|order| {
+            (0..order.len())
+                .map(|i| {
+                    let cube = order[..i]
+                        .iter()
+                        .fold(left, |cube, bit| cube.with_bit_of(*bit, right));
+                    cube.with_third_value(order[i], right)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn permutations(items: &[u64]) -> Vec<Vec<u64>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut all = vec![];
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let first = rest.remove(i);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, first);
+            all.push(permutation);
+        }
+    }
+    all
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::grammar::parse,
+        testing::{assert_equivalent, Generator},
+        traits::VarVisibility,
+    };
+
+    #[test]
+    fn parity_beats_sop() {
+        let exp = parse("a ^ b ^ c ^ d").unwrap();
+        let esop = minimize_esop(&exp).unwrap();
+        assert_eq!(esop.terms.len(), 4);
+
+        let comparison = compare_with_sop(&exp, &esop).unwrap();
+        assert_eq!(comparison.sop_terms, 8);
+        assert_eq!(comparison.sop_literals, 32);
+        assert_eq!(
+            comparison.to_string(),
+            "ESOP: 4 terms, 4 literals\nSOP:  8 terms, 32 literals"
+        );
+    }
+
+    #[test]
+    fn minimal_sop_for_comparison() {
+        let exp = parse("a b | ~a c").unwrap();
+        let comparison = compare_with_sop(&exp, &minimize_esop(&exp).unwrap()).unwrap();
+        assert_eq!((comparison.sop_terms, comparison.sop_literals), (2, 4));
+    }
+
+    #[test]
+    fn anf_start_without_polarity_search() {
+        // 13 variables, one more than the polarity search takes
+        let exp = parse("a ^ b ^ c ^ d ^ e ^ f ^ g ^ h ^ i ^ j ^ k ^ l ~m").unwrap();
+        assert!(best_polarity(&exp).is_err());
+        let esop = minimize_esop(&exp).unwrap();
+        assert_eq!(esop.terms.len(), 12);
+        assert_equivalent(&exp, &esop.to_expression());
+    }
+
+    #[test]
+    fn exorlink_improves_minterms() {
+        let names = parse("a b c").unwrap().get_sorted_variables();
+        // minterms of ~a ~b ~c | a b c | a ~b c, pairwise too far apart to merge
+        let cubes = [0b000, 0b111, 0b101]
+            .map(|value| Cube { care: 0b111, value })
+            .to_vec();
+        let linked = exorcise(cubes);
+        assert!(cost(&linked) < (3, 9));
+
+        let terms = linked.iter().map(|cube| cube.to_term(&names)).collect();
+        let esop = Esop { names, terms };
+        assert_equivalent(
+            &parse("~a ~b ~c | a b c | a ~b c").unwrap(),
+            &esop.to_expression(),
+        );
+    }
+
+    #[test]
+    fn equivalent_to_the_original() {
+        let mut generator = Generator(0xe50);
+        for _ in 0..100 {
+            let exp = generator.expression(3);
+            let esop = minimize_esop(&exp).unwrap();
+            assert_equivalent(&exp, &esop.to_expression());
+            assert!(esop.terms.len() <= best_polarity(&exp).unwrap().terms.len());
+        }
+    }
+}
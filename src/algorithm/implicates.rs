@@ -0,0 +1,133 @@
+use crate::{
+    algorithm::{
+        consensus::prime_implicants,
+        mintermmini::{expression_minterms, minimize, select_cover},
+        normalform::new_from_clauses,
+    },
+    expression::Expression,
+    operation::Operation,
+    operators::Not,
+    term::Term,
+    var::Var,
+};
+
+/// Every clause `exp` implies that stops being implied when any literal is removed, sorted.
+/// These are the prime implicants of the negation with every literal negated.
+pub fn prime_implicates(exp: &Expression) -> Result<Vec<Vec<Var>>, String> {
+    Ok(prime_implicants(&negation(exp))?
+        .iter()
+        .map(dual_clause)
+        .collect())
+}
+
+/// Product of sums with the fewest clauses, dual to the minimal sum of products: the rows
+/// where `exp` is `ZERO` get covered instead of the ones where it is `ONE`
+pub fn minimal_cnf(exp: &Expression) -> Result<Expression, String> {
+    let (names, maxterms) = expression_minterms(&negation(exp))?;
+    let terms: Vec<Term> = maxterms
+        .iter()
+        .map(|maxterm| Term::new_from_minterm_of(&names, *maxterm))
+        .collect();
+
+    let mut clauses: Vec<Vec<Var>> = select_cover(&minimize(&terms), &terms)
+        .iter()
+        .map(dual_clause)
+        .collect();
+    clauses.sort();
+    Ok(new_from_clauses(clauses))
+}
+
+fn negation(exp: &Expression) -> Expression {
+    Expression::Operation(Operation::NOT(Not(Box::new(exp.clone()))))
+}
+
+/// ~(a ~b) = ~a | b
+fn dual_clause(term: &Term) -> Vec<Var> {
+    term.vars().iter().map(|var| var.negate()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algorithm::normalform::clause_expression,
+        constant::Constant,
+        parser::grammar::parse,
+        scope::VarScope,
+        testing::Generator,
+        traits::{Eval, VarVisibility},
+        var::VarName,
+    };
+
+    fn is_implicate(clause: &[Var], exp: &Expression, names: &[VarName]) -> bool {
+        let clause = clause_expression(clause.to_vec());
+        (0..(1u64 << names.len())).all(|minterm| {
+            let scope = VarScope::new_from_minterm(names, minterm);
+            exp.evaluate(&scope) != Ok(Constant::ONE)
+                || clause.evaluate(&scope) == Ok(Constant::ONE)
+        })
+    }
+
+    #[test]
+    fn resolvents() {
+        let exp = parse("(a | b) & (~a | c)").unwrap();
+        let implicates: Vec<Expression> = prime_implicates(&exp)
+            .unwrap()
+            .into_iter()
+            .map(clause_expression)
+            .collect();
+        assert_eq!(
+            implicates,
+            [
+                parse("~a | c").unwrap(),
+                parse("a | b").unwrap(),
+                parse("b | c").unwrap()
+            ]
+        );
+        assert_eq!(
+            minimal_cnf(&exp).unwrap(),
+            parse("(a | b) & (~a | c)").unwrap()
+        );
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(
+            prime_implicates(&parse("a | ~a").unwrap()).unwrap().len(),
+            0
+        );
+        assert_eq!(prime_implicates(&parse("a ~a").unwrap()).unwrap(), [vec![]]);
+        assert_eq!(
+            minimal_cnf(&parse("a | ~a").unwrap()).unwrap(),
+            parse("1").unwrap()
+        );
+        assert_eq!(
+            minimal_cnf(&parse("a ~a").unwrap()).unwrap(),
+            parse("0").unwrap()
+        );
+    }
+
+    #[test]
+    fn all_implicates() {
+        let mut generator = Generator(0x1c8);
+        for _ in 0..100 {
+            let exp = generator.expression(3);
+            let names = exp.get_sorted_variables();
+            let implicates = prime_implicates(&exp).unwrap();
+
+            for clause in implicates.iter() {
+                assert!(is_implicate(clause, &exp, &names), "{}", exp);
+                for var in clause {
+                    let shorter: Vec<Var> = clause.iter().filter(|v| *v != var).copied().collect();
+                    assert!(!is_implicate(&shorter, &exp, &names), "{}", exp);
+                }
+            }
+
+            let cnf = minimal_cnf(&exp).unwrap();
+            for minterm in 0..(1u64 << names.len()) {
+                let scope = VarScope::new_from_minterm(&names, minterm);
+                assert_eq!(exp.evaluate(&scope), cnf.evaluate(&scope), "{}", exp);
+            }
+        }
+    }
+}
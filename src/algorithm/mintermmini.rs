@@ -1,14 +1,117 @@
-use crate::term::Term;
+use crate::{expression::Expression, term::Term, truthtable::TruthTable, var::VarName};
+
+/// The variables of `exp` and the minterms over them, in the order of `Term::new_from_minterm_of`
+pub fn expression_minterms(exp: &Expression) -> Result<(Vec<VarName>, Vec<u64>), String> {
+    let table = TruthTable::new_from_expression(exp)?;
+    Ok((table.names().to_vec(), table.minterms()))
+}
+
+/// Minimal sum of products equivalent to `exp`
+pub fn minimize_expression(exp: &Expression) -> Result<Expression, String> {
+    minimize_expression_observed(exp, &mut NoObserver)
+}
+
+pub fn minimize_expression_observed(
+    exp: &Expression,
+    observer: &mut dyn MinimizeObserver,
+) -> Result<Expression, String> {
+    Ok(Expression::new_from_terms(minimal_terms_observed(
+        exp, observer,
+    )?))
+}
+
+/// Terms of the minimal sum of products, sorted
+pub fn minimal_terms(exp: &Expression) -> Result<Vec<Term>, String> {
+    minimal_terms_observed(exp, &mut NoObserver)
+}
+
+/// The prime implicants come from Quine–McCluskey, then the fewest of them covering every
+/// minterm get selected
+pub fn minimal_terms_observed(
+    exp: &Expression,
+    observer: &mut dyn MinimizeObserver,
+) -> Result<Vec<Term>, String> {
+    let (names, minterms) = expression_minterms(exp)?;
+    let terms: Vec<Term> = minterms
+        .iter()
+        .map(|minterm| Term::new_from_minterm_of(&names, *minterm))
+        .collect();
+    let primes = minimize_observed(&terms, observer);
+    let mut cover = select_cover(&primes, &terms);
+    cover.sort();
+    Ok(cover)
+}
+
+/// What happens during minimization, for callers that want to follow along
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MinimizeEvent {
+    /// Iterations are counted from 1, `terms` are the ones the iteration starts with
+    IterationStart {
+        iteration: usize,
+        terms: Vec<Term>,
+    },
+    Merged {
+        left: Term,
+        right: Term,
+        merged: Term,
+    },
+    /// Found while trimming the merged terms, every minterm of `redundant` is one of `by`
+    Redundant {
+        redundant: Term,
+        by: Term,
+    },
+    IterationEnd {
+        iteration: usize,
+        merges: usize,
+    },
+}
+
+pub trait MinimizeObserver {
+    fn notify(&mut self, event: MinimizeEvent);
+}
+
+/// Ignores every event
+pub struct NoObserver;
+
+impl MinimizeObserver for NoObserver {
+    fn notify(&mut self, _event: MinimizeEvent) {}
+}
+
+/// Keeps every event in order
+#[derive(Debug, Default)]
+pub struct EventLog {
+    pub events: Vec<MinimizeEvent>,
+}
+
+impl MinimizeObserver for EventLog {
+    fn notify(&mut self, event: MinimizeEvent) {
+        self.events.push(event);
+    }
+}
 
 pub fn minimize(terms: &[Term]) -> Vec<Term> {
-    let (mut iteration, mut merges) = step_quine(terms);
+    minimize_observed(terms, &mut NoObserver)
+}
+
+/// Same as `minimize`, telling `observer` about every iteration, merge and redundant term
+pub fn minimize_observed(terms: &[Term], observer: &mut dyn MinimizeObserver) -> Vec<Term> {
+    let mut iteration = 1;
+    let (mut terms, mut merges) = step_quine(terms, iteration, observer);
     while merges > 0 {
-        (iteration, merges) = step_quine(&iteration);
+        iteration += 1;
+        (terms, merges) = step_quine(&terms, iteration, observer);
     }
-    iteration
+    terms
 }
 
 pub fn trim_redundant_terms(terms: &[Term]) -> Vec<Term> {
+    trim_redundant_terms_observed(terms, &mut NoObserver)
+}
+
+pub fn trim_redundant_terms_observed(
+    terms: &[Term],
+    observer: &mut dyn MinimizeObserver,
+) -> Vec<Term> {
     let mut is_redundant: Vec<bool> = vec![false; terms.len()];
     for (ind1, term1) in terms.iter().enumerate() {
         if is_redundant[ind1] {
@@ -20,15 +123,17 @@ pub fn trim_redundant_terms(terms: &[Term]) -> Vec<Term> {
             }
 
             if let Some(redundant) = Term::is_one_redundant(term1, term2) {
-                println!(
-                    "Between '{}' and '{}' the #{} is redundant",
-                    term1, term2, redundant
-                );
-                if redundant == 1 {
+                let (redundant, by) = if redundant == 1 {
                     is_redundant[ind1] = true;
+                    (term1, term2)
                 } else {
                     is_redundant[ind2] = true;
-                }
+                    (term2, term1)
+                };
+                observer.notify(MinimizeEvent::Redundant {
+                    redundant: redundant.clone(),
+                    by: by.clone(),
+                });
             }
         }
     }
@@ -41,7 +146,65 @@ pub fn trim_redundant_terms(terms: &[Term]) -> Vec<Term> {
         .collect()
 }
 
-fn step_quine(terms: &[Term]) -> (Vec<Term>, usize) {
+/// Fewest of `implicants` that together cover all of `minterms`, ties go to fewer literals.
+/// Branches on the uncovered minterm with the fewest implicants, so essential ones come first.
+pub fn select_cover(implicants: &[Term], minterms: &[Term]) -> Vec<Term> {
+    let covering: Vec<Vec<usize>> = minterms
+        .iter()
+        .map(|minterm| {
+            (0..implicants.len())
+                .filter(|i| implicants[*i].covers(minterm))
+                .collect()
+        })
+        .collect();
+
+    let mut best = None;
+    branch_cover(implicants, &covering, &mut vec![], &mut best);
+    best.unwrap_or_default()
+        .into_iter()
+        .map(|i| implicants[i].clone())
+        .collect()
+}
+
+fn branch_cover(
+    implicants: &[Term],
+    covering: &[Vec<usize>],
+    chosen: &mut Vec<usize>,
+    best: &mut Option<Vec<usize>>,
+) {
+    let cost = |chosen: &[usize]| {
+        let literals: usize = chosen.iter().map(|i| implicants[*i].vars().len()).sum();
+        (chosen.len(), literals)
+    };
+    if best.as_ref().is_some_and(|best| cost(chosen) >= cost(best)) {
+        return;
+    }
+
+    let uncovered = covering
+        .iter()
+        .filter(|options| !options.iter().any(|i| chosen.contains(i)))
+        .min_by_key(|options| options.len());
+    let Some(options) = uncovered else {
+        *best = Some(chosen.clone());
+        return;
+    };
+
+    for i in options {
+        chosen.push(*i);
+        branch_cover(implicants, covering, chosen, best);
+        chosen.pop();
+    }
+}
+
+fn step_quine(
+    terms: &[Term],
+    iteration: usize,
+    observer: &mut dyn MinimizeObserver,
+) -> (Vec<Term>, usize) {
+    observer.notify(MinimizeEvent::IterationStart {
+        iteration,
+        terms: terms.to_vec(),
+    });
     let mut merges = 0;
     let mut merged_pairs: Vec<Term> = vec![];
     let mut has_been_used: Vec<bool> = vec![false; terms.len()];
@@ -53,7 +216,11 @@ fn step_quine(terms: &[Term]) -> (Vec<Term>, usize) {
                 Some(merged) => {
                     has_been_used[ind1] = true;
                     has_been_used[ind2] = true;
-                    println!("Merged '{}' + '{}' => '{}'", term1, term2, merged);
+                    observer.notify(MinimizeEvent::Merged {
+                        left: term1.clone(),
+                        right: term2.clone(),
+                        merged: merged.clone(),
+                    });
                     merged_pairs.push(merged);
                     merges += 1;
                 }
@@ -64,5 +231,86 @@ fn step_quine(terms: &[Term]) -> (Vec<Term>, usize) {
         }
     }
 
-    (trim_redundant_terms(&merged_pairs), merges)
+    let trimmed = trim_redundant_terms_observed(&merged_pairs, observer);
+    observer.notify(MinimizeEvent::IterationEnd { iteration, merges });
+    (trimmed, merges)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::grammar::parse;
+
+    #[test]
+    fn cover() {
+        let exp = parse("a b | ~a c").unwrap();
+        let (names, minterms) = expression_minterms(&exp).unwrap();
+        let minterms: Vec<Term> = minterms
+            .iter()
+            .map(|minterm| Term::new_from_minterm_of(&names, *minterm))
+            .collect();
+
+        let primes = minimize(&minterms);
+        assert_eq!(primes.len(), 3);
+        assert_eq!(
+            Expression::new_from_terms(select_cover(&primes, &minterms)),
+            parse("~a c | a b").unwrap()
+        );
+    }
+
+    #[test]
+    fn minimal_results() {
+        let minimized = |source: &str| minimize_expression(&parse(source).unwrap()).unwrap();
+        // the consensus term b c is a prime implicant, but not needed
+        assert_eq!(minimized("a b | ~a c"), parse("a b | ~a c").unwrap());
+        assert_eq!(
+            minimized("a b c | a b ~c | a ~b c | ~a b c"),
+            parse("a b | a c | b c").unwrap()
+        );
+        assert_eq!(minimized("a ~a"), parse("0").unwrap());
+    }
+
+    #[test]
+    fn events() {
+        let exp = parse("a b | a ~b").unwrap();
+        let mut log = EventLog::default();
+        assert_eq!(
+            minimize_expression_observed(&exp, &mut log).unwrap(),
+            parse("a").unwrap()
+        );
+
+        let terms = |source: &str| match parse(source).unwrap() {
+            Expression::Term(term) => term,
+            other => Term::new_from_vars(vec![match other {
+                Expression::Var(var) => var,
+                _ => unreachable!(),
+            }]),
+        };
+        assert_eq!(
+            log.events,
+            vec![
+                MinimizeEvent::IterationStart {
+                    iteration: 1,
+                    terms: vec![terms("a ~b"), terms("a b")],
+                },
+                MinimizeEvent::Merged {
+                    left: terms("a ~b"),
+                    right: terms("a b"),
+                    merged: terms("a"),
+                },
+                MinimizeEvent::IterationEnd {
+                    iteration: 1,
+                    merges: 1,
+                },
+                MinimizeEvent::IterationStart {
+                    iteration: 2,
+                    terms: vec![terms("a")],
+                },
+                MinimizeEvent::IterationEnd {
+                    iteration: 2,
+                    merges: 0,
+                },
+            ]
+        );
+    }
 }
@@ -1 +1,8 @@
+pub mod consensus;
+pub mod esop;
+pub mod implicates;
 pub mod mintermmini;
+pub mod normalform;
+pub mod reedmuller;
+pub mod rewrite;
+pub mod simulation;
@@ -0,0 +1,285 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    term::Term,
+    traits::VariableLengthOperator,
+    var::Var,
+};
+
+/// Terms of a DNF, or clauses of a CNF, a conversion may produce before giving up.
+/// Distribution can grow exponentially, like for a chain of XORs.
+pub const MAX_TERMS: usize = 4096;
+
+/// Nodes an NNF may have before giving up. Every XOR expands the rest of its chain twice,
+/// so a chain of n operands takes about 6·2ⁿ nodes.
+pub const MAX_NODES: usize = 1 << 16;
+
+/// Negation normal form: only AND and OR, negations only on variables
+pub fn nnf(exp: &Expression, max_nodes: usize) -> Result<Expression, String> {
+    nnf_of(exp, false, &mut max_nodes.clone())
+}
+
+/// NNF of `exp`, or of its negation. Every call builds at least one node, `budget` counts
+/// how many more may be built.
+fn nnf_of(exp: &Expression, negated: bool, budget: &mut usize) -> Result<Expression, String> {
+    *budget = budget.checked_sub(1).ok_or_else(|| {
+        String::from("The negation normal form has too many nodes, that's too big to build!")
+    })?;
+
+    Ok(match exp {
+        Expression::Constant(constant) => Expression::Constant(constant.negate_if(negated)),
+        Expression::Var(var) if negated => Expression::Var(var.negate()),
+        Expression::Var(var) => Expression::Var(*var),
+        Expression::Term(term) if negated => match term.vars() {
+            [] => Expression::Constant(Constant::ZERO),
+            [var] => Expression::Var(var.negate()),
+            vars => join::<Or>(
+                vars.iter()
+                    .map(|var| Expression::Var(var.negate()))
+                    .collect(),
+            ),
+        },
+        Expression::Term(term) => Expression::Term(term.clone()),
+        Expression::Operation(Operation::NOT(Not(inner))) => nnf_of(inner, !negated, budget)?,
+        Expression::Operation(Operation::AND(And(operands))) => {
+            let operands = operands
+                .iter()
+                .map(|exp| nnf_of(exp, negated, budget))
+                .collect::<Result<_, _>>()?;
+            if negated {
+                join::<Or>(operands)
+            } else {
+                join::<And>(operands)
+            }
+        }
+        Expression::Operation(Operation::OR(Or(operands))) => {
+            let operands = operands
+                .iter()
+                .map(|exp| nnf_of(exp, negated, budget))
+                .collect::<Result<_, _>>()?;
+            if negated {
+                join::<And>(operands)
+            } else {
+                join::<Or>(operands)
+            }
+        }
+        Expression::Operation(Operation::XOR(Xor(operands))) => match operands.as_slice() {
+            [] => Expression::Constant(Constant::ZERO.negate_if(negated)),
+            [operand] => nnf_of(operand, negated, budget)?,
+            [first, rest @ ..] => {
+                // a ^ b = a ~b | ~a b and ~(a ^ b) = a b | ~a ~b
+                let rest = Xor::new(rest.to_vec()).into_expression();
+                join::<Or>(vec![
+                    join::<And>(vec![
+                        nnf_of(first, false, budget)?,
+                        nnf_of(&rest, !negated, budget)?,
+                    ]),
+                    join::<And>(vec![
+                        nnf_of(first, true, budget)?,
+                        nnf_of(&rest, negated, budget)?,
+                    ]),
+                ])
+            }
+        },
+    })
+}
+
+/// Operation over `operands`, nested operations of the same operator get merged into it
+fn join<O: VariableLengthOperator>(operands: Vec<Expression>) -> Expression {
+    let mut flat = vec![];
+    for exp in operands.into_iter() {
+        match O::try_from_expression(exp) {
+            Ok(inner) => flat.append(&mut inner.into_operands()),
+            Err(exp) => flat.push(exp),
+        }
+    }
+    O::new(flat).into_expression()
+}
+
+/// Products of a disjunctive normal form, without contradicting or absorbed terms
+pub fn dnf_terms(exp: &Expression, max_terms: usize) -> Result<Vec<Term>, String> {
+    let mut terms = distribute(&nnf(exp, MAX_NODES)?, max_terms)?;
+    terms.sort();
+    Ok(terms)
+}
+
+/// Disjunctive normal form, a sum of products
+pub fn dnf(exp: &Expression, max_terms: usize) -> Result<Expression, String> {
+    Ok(Expression::new_from_terms(dnf_terms(exp, max_terms)?))
+}
+
+/// Clauses of a conjunctive normal form, each clause is the OR of its literals.
+/// These are the terms of the DNF of the negation, negated.
+pub fn cnf_clauses(exp: &Expression, max_terms: usize) -> Result<Vec<Vec<Var>>, String> {
+    let negation = Expression::Operation(Operation::NOT(Not(Box::new(exp.clone()))));
+    Ok(dnf_terms(&negation, max_terms)?
+        .iter()
+        .map(|term| term.vars().iter().map(|var| var.negate()).collect())
+        .collect())
+}
+
+/// Conjunctive normal form, a product of sums
+pub fn cnf(exp: &Expression, max_terms: usize) -> Result<Expression, String> {
+    Ok(new_from_clauses(cnf_clauses(exp, max_terms)?))
+}
+
+/// AND of the clauses, no clauses at all are 1
+pub fn new_from_clauses(clauses: Vec<Vec<Var>>) -> Expression {
+    let mut clauses: Vec<Expression> = clauses.into_iter().map(clause_expression).collect();
+    match clauses.len() {
+        0 => Expression::Constant(Constant::ONE),
+        1 => clauses.pop().unwrap(),
+        _ => And::new(clauses).into_expression(),
+    }
+}
+
+/// OR of the literals, the empty clause is 0
+pub fn clause_expression(clause: Vec<Var>) -> Expression {
+    match clause.as_slice() {
+        [] => Expression::Constant(Constant::ZERO),
+        [var] => Expression::Var(*var),
+        _ => Or::new(clause.into_iter().map(Expression::Var).collect()).into_expression(),
+    }
+}
+
+/// Terms of an NNF expression, distributing AND over OR
+fn distribute(exp: &Expression, max_terms: usize) -> Result<Vec<Term>, String> {
+    let terms = match exp {
+        Expression::Constant(Constant::ZERO) => vec![],
+        Expression::Constant(Constant::ONE) => vec![Term::new_from_vars(vec![])],
+        Expression::Var(var) => vec![Term::new_from_vars(vec![*var])],
+        Expression::Term(term) => Term::new_from_vars(vec![])
+            .conjoin(term)
+            .into_iter()
+            .collect(),
+        Expression::Operation(Operation::OR(Or(operands))) => {
+            let mut terms = vec![];
+            for operand in operands.iter() {
+                terms.append(&mut distribute(operand, max_terms)?);
+                check_size(terms.len(), max_terms)?;
+            }
+            terms
+        }
+        Expression::Operation(Operation::AND(And(operands))) => {
+            let mut terms = vec![Term::new_from_vars(vec![])];
+            for operand in operands.iter() {
+                let operand_terms = distribute(operand, max_terms)?;
+                check_size(terms.len() * operand_terms.len(), max_terms)?;
+                terms = terms
+                    .iter()
+                    .flat_map(|term| operand_terms.iter().filter_map(|other| term.conjoin(other)))
+                    .collect();
+                terms = absorb(terms);
+            }
+            terms
+        }
+        _ => unreachable!("NNF only has AND, OR and literals, got {:?}", exp),
+    };
+    Ok(absorb(terms))
+}
+
+fn check_size(len: usize, max_terms: usize) -> Result<(), String> {
+    if len > max_terms {
+        Err(format!(
+            "The normal form has more than {} terms, that's too big to build!",
+            max_terms
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Drop duplicates and every term another one covers, like a b next to a
+fn absorb(mut terms: Vec<Term>) -> Vec<Term> {
+    terms.sort_by_key(|term| term.vars().len());
+    let mut kept: Vec<Term> = vec![];
+    for term in terms.into_iter() {
+        if !kept.iter().any(|other| other.covers(&term)) {
+            kept.push(term);
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::grammar::parse,
+        testing::{assert_equivalent, Generator},
+    };
+
+    fn is_nnf(exp: &Expression) -> bool {
+        match exp {
+            Expression::Operation(Operation::AND(And(operands)))
+            | Expression::Operation(Operation::OR(Or(operands))) => operands.iter().all(is_nnf),
+            Expression::Operation(_) => false,
+            _ => true,
+        }
+    }
+
+    #[test]
+    fn negation_normal_form() {
+        let exp = parse("~(a & ~(b | c d)) ^ c").unwrap();
+        let converted = nnf(&exp, MAX_NODES).unwrap();
+        assert!(is_nnf(&converted));
+        assert_equivalent(&exp, &converted);
+        assert_eq!(
+            nnf(&parse("~(a b | ~c)").unwrap(), MAX_NODES).unwrap(),
+            parse("(~a | ~b) & c").unwrap()
+        );
+    }
+
+    #[test]
+    fn conjunctive_and_disjunctive() {
+        let exp = parse("(a | b) & (c | ~a)").unwrap();
+        assert_eq!(
+            dnf(&exp, MAX_TERMS).unwrap(),
+            parse("a c | ~a b | b c").unwrap()
+        );
+        assert_eq!(
+            cnf(&parse("a b | c").unwrap(), MAX_TERMS).unwrap(),
+            parse("(a | c) & (b | c)").unwrap()
+        );
+        assert_eq!(
+            cnf(&parse("a | ~a").unwrap(), MAX_TERMS).unwrap(),
+            parse("1").unwrap()
+        );
+        assert_eq!(
+            dnf(&parse("a & ~a").unwrap(), MAX_TERMS).unwrap(),
+            parse("0").unwrap()
+        );
+    }
+
+    #[test]
+    fn equivalent_to_the_original() {
+        let mut generator = Generator(0xc4f);
+        for _ in 0..200 {
+            let exp = generator.expression(3);
+            assert_equivalent(&exp, &dnf(&exp, MAX_TERMS).unwrap());
+            assert_equivalent(&exp, &cnf(&exp, MAX_TERMS).unwrap());
+        }
+    }
+
+    #[test]
+    fn wide_xor_fails_early() {
+        let source = (1..=22)
+            .map(|i| format!("x{}", i))
+            .collect::<Vec<String>>()
+            .join(" ^ ");
+        let exp = parse(&source).unwrap();
+
+        assert!(nnf(&exp, MAX_NODES).is_err());
+        assert!(dnf(&exp, MAX_TERMS).is_err());
+        assert!(cnf(&exp, MAX_TERMS).is_err());
+    }
+
+    #[test]
+    fn size_guard() {
+        let exp = parse("a1 ^ b1 ^ x1 ^ a ^ b ^ x ^ a12 ^ b12 ^ x12").unwrap();
+        assert!(dnf(&exp, 100).is_err());
+        assert_eq!(dnf_terms(&exp, MAX_TERMS).unwrap().len(), 256);
+    }
+}
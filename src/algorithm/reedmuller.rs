@@ -0,0 +1,212 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operators::Xor,
+    term::Term,
+    traits::VariableLengthOperator,
+    truthtable::TruthTable,
+    var::{Var, VarName},
+};
+
+// every polarity gets looked at, that's 2^n transforms of 2^n coefficients
+const MAX_POLARITY_VARIABLES: usize = 12;
+
+/// XOR of products where every variable appears with one fixed polarity. With all variables
+/// positive it is the algebraic normal form, the Zhegalkin polynomial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReedMuller {
+    pub names: Vec<VarName>,
+    /// Bits in the order of minterms, the first name is the most significant bit. A set bit
+    /// means the variable only appears negated.
+    pub polarity: u64,
+    pub terms: Vec<Term>,
+}
+
+impl ReedMuller {
+    pub fn literal_count(&self) -> usize {
+        self.terms.iter().map(|term| term.vars().len()).sum()
+    }
+
+    pub fn to_expression(&self) -> Expression {
+        xor_of_terms(&self.terms)
+    }
+}
+
+/// XOR of the terms, an empty term is 1 and no terms at all are 0
+pub fn xor_of_terms(terms: &[Term]) -> Expression {
+    let mut operands: Vec<Expression> = terms
+        .iter()
+        .map(|term| match term.vars() {
+            [] => Expression::Constant(Constant::ONE),
+            [var] => Expression::Var(*var),
+            _ => Expression::Term(term.clone()),
+        })
+        .collect();
+
+    match operands.len() {
+        0 => Expression::Constant(Constant::ZERO),
+        1 => operands.pop().unwrap(),
+        _ => Xor::new(operands).into_expression(),
+    }
+}
+
+/// Algebraic normal form, XOR of ANDs of positive variables
+pub fn anf(exp: &Expression) -> Result<ReedMuller, String> {
+    reed_muller(exp, 0)
+}
+
+pub fn reed_muller(exp: &Expression, polarity: u64) -> Result<ReedMuller, String> {
+    let (names, mut coefficients) = truth_vector(exp)?;
+    if polarity >> names.len() != 0 {
+        return Err(format!(
+            "Polarity {:b} has more bits than the {} variables!",
+            polarity,
+            names.len()
+        ));
+    }
+
+    // with x negated, f(x) is g(~x) and g's ANF is the one wanted
+    coefficients = (0..coefficients.len())
+        .map(|i| coefficients[i ^ polarity as usize])
+        .collect();
+    mobius(&mut coefficients);
+    Ok(from_coefficients(names, polarity, &coefficients))
+}
+
+/// The fixed polarity form with the fewest terms, then the fewest literals
+pub fn best_polarity(exp: &Expression) -> Result<ReedMuller, String> {
+    let (names, mut coefficients) = truth_vector(exp)?;
+    if names.len() > MAX_POLARITY_VARIABLES {
+        return Err(format!(
+            "Expression has {} variables, searching polarities supports at most {}!",
+            names.len(),
+            MAX_POLARITY_VARIABLES
+        ));
+    }
+    mobius(&mut coefficients);
+
+    let cost = |coefficients: &[bool]| {
+        let terms = coefficients.iter().filter(|c| **c).count();
+        let literals: u32 = (0..coefficients.len())
+            .filter(|m| coefficients[*m])
+            .map(|m| m.count_ones())
+            .sum();
+        (terms, literals)
+    };
+
+    let mut best = (cost(&coefficients), 0u64);
+    let mut polarity = 0u64;
+    // Gray code order, every next polarity flips a single variable
+    for step in 1..(1u64 << names.len()) {
+        let bit = 1 << step.trailing_zeros();
+        polarity ^= bit;
+        flip_polarity(&mut coefficients, bit as usize);
+        best = best.min((cost(&coefficients), polarity));
+    }
+
+    reed_muller(exp, best.1)
+}
+
+fn truth_vector(exp: &Expression) -> Result<(Vec<VarName>, Vec<bool>), String> {
+    let table = TruthTable::new_from_expression(exp)?;
+    let values = (0..table.rows())
+        .map(|row| table.get(row) == Constant::ONE)
+        .collect();
+    Ok((table.names().to_vec(), values))
+}
+
+/// Truth table to ANF coefficients over GF(2), the transform is its own inverse
+fn mobius(values: &mut [bool]) {
+    let mut bit = 1;
+    while bit < values.len() {
+        for m in 0..values.len() {
+            if m & bit != 0 {
+                values[m] ^= values[m & !bit];
+            }
+        }
+        bit <<= 1;
+    }
+}
+
+/// Switch the variable at `bit` to the other polarity: x = 1 ^ ~x, so every product with
+/// it also adds the product without it
+fn flip_polarity(coefficients: &mut [bool], bit: usize) {
+    for m in 0..coefficients.len() {
+        if m & bit != 0 {
+            coefficients[m & !bit] ^= coefficients[m];
+        }
+    }
+}
+
+fn from_coefficients(names: Vec<VarName>, polarity: u64, coefficients: &[bool]) -> ReedMuller {
+    let len = names.len();
+    let terms = (0..coefficients.len())
+        .filter(|m| coefficients[*m])
+        .map(|m| {
+            let vars = (0..len)
+                .filter(|i| (m >> (len - 1 - i)) & 1 == 1)
+                .map(|i| Var::new_from_name(names[i], (polarity >> (len - 1 - i)) & 1 == 1))
+                .collect();
+            Term::new_from_vars(vars)
+        })
+        .collect();
+
+    ReedMuller {
+        names,
+        polarity,
+        terms,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::grammar::parse,
+        testing::{assert_equivalent, Generator},
+    };
+
+    #[test]
+    fn algebraic_normal_form() {
+        // a | b = a ^ b ^ a b
+        assert_eq!(
+            anf(&parse("a | b").unwrap()).unwrap().to_expression(),
+            parse("b ^ a ^ a b").unwrap()
+        );
+        assert_eq!(
+            anf(&parse("~a").unwrap()).unwrap().to_expression(),
+            parse("1 ^ a").unwrap()
+        );
+        assert_eq!(
+            anf(&parse("a ~a").unwrap()).unwrap().to_expression(),
+            parse("0").unwrap()
+        );
+    }
+
+    #[test]
+    fn fixed_polarity() {
+        let exp = parse("~a ~b").unwrap();
+        assert_eq!(anf(&exp).unwrap().terms.len(), 4);
+
+        let best = best_polarity(&exp).unwrap();
+        assert_eq!(best.polarity, 0b11);
+        assert_eq!(best.to_expression(), parse("~a ~b").unwrap());
+        assert!(reed_muller(&exp, 0b100).is_err());
+    }
+
+    #[test]
+    fn equivalent_to_the_original() {
+        let mut generator = Generator(0xa9f);
+        for _ in 0..100 {
+            let exp = generator.expression(3);
+            assert_equivalent(&exp, &anf(&exp).unwrap().to_expression());
+            let best = best_polarity(&exp).unwrap();
+            assert_equivalent(&exp, &best.to_expression());
+            let fewest = (0..(1u64 << best.names.len()))
+                .map(|polarity| reed_muller(&exp, polarity).unwrap().terms.len())
+                .min()
+                .unwrap();
+            assert_eq!(best.terms.len(), fewest, "{}", exp);
+        }
+    }
+}
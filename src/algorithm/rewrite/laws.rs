@@ -0,0 +1,80 @@
+use super::Law;
+
+/// Name, left side and right side. Dual forms share their name, so they get enabled and
+/// disabled together.
+const STANDARD_LAWS: &[(&str, &str, &str)] = &[
+    ("Double negation", "~~A", "A"),
+    ("Negated constant", "~0", "1"),
+    ("Negated constant", "~1", "0"),
+    ("Identity", "A & 1", "A"),
+    ("Identity", "A | 0", "A"),
+    ("Identity", "A ^ 0", "A"),
+    ("Annihilation", "A & 0", "0"),
+    ("Annihilation", "A | 1", "1"),
+    ("Idempotence", "A & A", "A"),
+    ("Idempotence", "A | A", "A"),
+    ("Complement", "A & ~A", "0"),
+    ("Complement", "A | ~A", "1"),
+    ("XOR cancellation", "A ^ A", "0"),
+    ("XOR complement", "A ^ ~A", "1"),
+    ("XOR negation", "A ^ 1", "~A"),
+    ("Absorption", "A | A & B", "A"),
+    ("Absorption", "A & (A | B)", "A"),
+    ("Combining", "A & B | A & ~B", "A"),
+    ("Combining", "(A | B) & (A | ~B)", "A"),
+    ("Consensus", "A & B | ~A & C | B & C", "A & B | ~A & C"),
+    (
+        "Consensus",
+        "(A | B) & (~A | C) & (B | C)",
+        "(A | B) & (~A | C)",
+    ),
+    ("De Morgan", "~(A & B)", "~A | ~B"),
+    ("De Morgan", "~(A | B)", "~A & ~B"),
+    // only towards sums of products, together with its dual no strategy would terminate
+    ("Distribution", "A & (B | C)", "A & B | A & C"),
+];
+
+pub fn standard_laws() -> Vec<Law> {
+    STANDARD_LAWS
+        .iter()
+        .map(|(name, lhs, rhs)| Law::new(name, lhs, rhs).expect("Standard laws are valid"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algorithm::rewrite::pattern::Bindings, scope::VarValue, traits::Eval,
+        traits::VarVisibility, var::Var, var::VarName,
+    };
+
+    #[test]
+    fn laws_are_equivalences() {
+        for law in standard_laws() {
+            let mut names = vec![];
+            law.lhs().metavariables(&mut names);
+            let bindings: Bindings = names
+                .iter()
+                .map(|name| {
+                    let lowercase = VarName::new_from_identifier(&name.identifier().to_lowercase());
+                    let var = Var::new_from_name(lowercase, false);
+                    (*name, crate::expression::Expression::Var(var))
+                })
+                .collect();
+
+            let lhs = law.lhs().instantiate(&bindings);
+            let rhs = law.rhs().instantiate(&bindings);
+            let vars: Vec<VarName> = lhs.get_sorted_variables();
+            for minterm in 0..(1u64 << vars.len()) {
+                let scope = VarValue::new_from_minterm(&vars, minterm);
+                assert_eq!(
+                    lhs.evaluate(&scope),
+                    rhs.evaluate(&scope),
+                    "{} doesn't hold",
+                    law.name()
+                );
+            }
+        }
+    }
+}
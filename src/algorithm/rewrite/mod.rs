@@ -0,0 +1,402 @@
+pub mod laws;
+pub mod pattern;
+
+use std::collections::HashSet;
+
+use crate::{
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    traits::VariableLengthOperator,
+};
+
+use pattern::Pattern;
+
+/// Operand indices from the root down to a subexpression, the root is the empty path
+pub type Position = Vec<usize>;
+
+/// A named equivalence, used to rewrite its left side into its right side
+#[derive(Clone, Debug)]
+pub struct Law {
+    name: String,
+    lhs: Pattern,
+    rhs: Pattern,
+}
+
+impl Law {
+    pub fn new(name: &str, lhs: &str, rhs: &str) -> Result<Law, String> {
+        let lhs = Pattern::parse(lhs)?;
+        let rhs = Pattern::parse(rhs)?;
+
+        let mut bound = vec![];
+        lhs.metavariables(&mut bound);
+        let mut used = vec![];
+        rhs.metavariables(&mut used);
+        if let Some(unbound) = used.iter().find(|name| !bound.contains(name)) {
+            return Err(format!(
+                "Law '{}' uses '{}' on the right side only!",
+                name,
+                unbound.get_identifier()
+            ));
+        }
+
+        Ok(Law {
+            name: name.to_string(),
+            lhs,
+            rhs,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn lhs(&self) -> &Pattern {
+        &self.lhs
+    }
+    pub fn rhs(&self) -> &Pattern {
+        &self.rhs
+    }
+
+    /// Rewrite the top of `exp`, None if the left side doesn't match there
+    pub fn apply(&self, exp: &Expression) -> Option<Expression> {
+        let found = self.lhs.match_top(exp)?;
+        Some(self.rhs.replace(&self.lhs, found))
+    }
+}
+
+/// One rewrite of a derivation, `before` and `after` are the whole expression
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub before: Expression,
+    pub after: Expression,
+    pub law: String,
+    pub position: Position,
+}
+
+/// Which of the possible rewrites gets done next. Every strategy repeats until no enabled law
+/// applies anymore.
+#[derive(Clone, Copy, Debug)]
+pub enum Strategy {
+    /// Leftmost of the deepest subexpressions first
+    Innermost,
+    /// Leftmost of the subexpressions closest to the root first
+    Outermost,
+    /// The rewrite anywhere that leaves the cheapest expression, as long as it is cheaper than
+    /// the current one
+    Fixpoint(fn(&Expression) -> usize),
+}
+
+/// Number of literals, constants and operators, terms count like an AND of their literals
+pub fn size(exp: &Expression) -> usize {
+    match exp {
+        Expression::Constant(_) | Expression::Var(_) => 1,
+        Expression::Term(term) => match term.vars().len() {
+            0 | 1 => 1,
+            len => len + 1,
+        },
+        other => 1 + children(other).into_iter().map(size).sum::<usize>(),
+    }
+}
+
+/// Laws, which of them are enabled and how many rewrites are allowed before giving up
+#[derive(Clone, Debug)]
+pub struct Rewriter {
+    laws: Vec<Law>,
+    disabled: HashSet<String>,
+    max_steps: usize,
+}
+
+impl Rewriter {
+    pub fn new(laws: Vec<Law>) -> Self {
+        Rewriter {
+            laws,
+            disabled: HashSet::new(),
+            max_steps: 1000,
+        }
+    }
+
+    /// All of `laws::standard_laws`
+    pub fn standard() -> Self {
+        Self::new(laws::standard_laws())
+    }
+
+    pub fn laws(&self) -> &[Law] {
+        &self.laws
+    }
+    pub fn add_law(&mut self, law: Law) {
+        self.laws.push(law);
+    }
+
+    /// Disables every law called `name`
+    pub fn disable(&mut self, name: &str) -> Result<(), String> {
+        self.check_known(name)?;
+        self.disabled.insert(name.to_string());
+        Ok(())
+    }
+    pub fn enable(&mut self, name: &str) -> Result<(), String> {
+        self.check_known(name)?;
+        self.disabled.remove(name);
+        Ok(())
+    }
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.contains(name)
+    }
+
+    fn check_known(&self, name: &str) -> Result<(), String> {
+        if self.laws.iter().any(|law| law.name == name) {
+            Ok(())
+        } else {
+            Err(format!("There is no law called '{}'!", name))
+        }
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = max_steps;
+    }
+
+    /// Rewrite `exp` with the enabled laws until none applies, failing if that takes more
+    /// than the maximum number of steps, like when two laws undo each other
+    pub fn rewrite(&self, exp: &Expression, strategy: Strategy) -> Result<Expression, String> {
+        let steps = self.rewrite_steps(exp, strategy)?;
+        Ok(steps
+            .last()
+            .map_or_else(|| exp.clone(), |step| step.after.clone()))
+    }
+
+    /// Same as `rewrite`, but returns every step taken, no steps if no law applies at all
+    pub fn rewrite_steps(&self, exp: &Expression, strategy: Strategy) -> Result<Vec<Step>, String> {
+        let mut steps: Vec<Step> = vec![];
+        let mut exp = exp.clone();
+        for _ in 0..self.max_steps {
+            let Some((law, position, after)) = self.next_rewrite(&exp, strategy) else {
+                return Ok(steps);
+            };
+            steps.push(Step {
+                before: exp,
+                after: after.clone(),
+                law: law.name.clone(),
+                position,
+            });
+            exp = after;
+        }
+        Err(format!(
+            "Gave up after {} rewrites, the laws might undo each other!",
+            self.max_steps
+        ))
+    }
+
+    /// The law, where it applies and the whole expression after applying it
+    fn next_rewrite(
+        &self,
+        exp: &Expression,
+        strategy: Strategy,
+    ) -> Option<(&Law, Position, Expression)> {
+        let mut positions = vec![];
+        collect_positions(exp, &mut vec![], &mut positions);
+        // stable sorts, positions of the same depth stay in left to right order
+        if matches!(strategy, Strategy::Innermost) {
+            positions.sort_by_key(|position| std::cmp::Reverse(position.len()));
+        } else if matches!(strategy, Strategy::Outermost) {
+            positions.sort_by_key(|position| position.len());
+        }
+
+        let mut candidates = positions.into_iter().flat_map(|position| {
+            let sub = subexpression(exp, &position);
+            self.laws
+                .iter()
+                .filter(|law| self.is_enabled(&law.name))
+                .filter_map(move |law| law.apply(sub).map(|new| (law, position.clone(), new)))
+                .map(|(law, position, new)| {
+                    let rewritten = replace_at(exp, &position, new);
+                    (law, position, rewritten)
+                })
+        });
+
+        match strategy {
+            Strategy::Innermost | Strategy::Outermost => candidates.next(),
+            Strategy::Fixpoint(cost) => {
+                let current = cost(exp);
+                candidates
+                    .map(|candidate| (cost(&candidate.2), candidate))
+                    .filter(|(new_cost, _)| *new_cost < current)
+                    .min_by_key(|(new_cost, _)| *new_cost)
+                    .map(|(_, candidate)| candidate)
+            }
+        }
+    }
+}
+
+/// Operands of an operation, literals and constants have none
+pub fn children(exp: &Expression) -> Vec<&Expression> {
+    match exp {
+        Expression::Operation(Operation::NOT(Not(inner))) => vec![inner.as_ref()],
+        Expression::Operation(
+            Operation::AND(And(operands))
+            | Operation::OR(Or(operands))
+            | Operation::XOR(Xor(operands)),
+        ) => operands.iter().collect(),
+        _ => vec![],
+    }
+}
+
+/// Every position in `exp`, parents before their children
+fn collect_positions(exp: &Expression, path: &mut Position, out: &mut Vec<Position>) {
+    out.push(path.clone());
+    for (i, child) in children(exp).into_iter().enumerate() {
+        path.push(i);
+        collect_positions(child, path, out);
+        path.pop();
+    }
+}
+
+pub fn subexpression<'a>(exp: &'a Expression, position: &[usize]) -> &'a Expression {
+    match position.split_first() {
+        None => exp,
+        Some((i, rest)) => subexpression(children(exp)[*i], rest),
+    }
+}
+
+/// Copy of `exp` with the subexpression at `position` replaced by `new`. A replacement with
+/// the same operator as its parent gets merged into it.
+pub fn replace_at(exp: &Expression, position: &[usize], new: Expression) -> Expression {
+    let Some((i, rest)) = position.split_first() else {
+        return new;
+    };
+    let replace_operand = |operands: &[Expression],
+                           merge: fn(Expression) -> Result<Vec<Expression>, Expression>|
+     -> Vec<Expression> {
+        let mut operands = operands.to_vec();
+        match merge(replace_at(&operands[*i], rest, new.clone())) {
+            Ok(inner) => {
+                operands.splice(*i..=*i, inner);
+            }
+            Err(replaced) => operands[*i] = replaced,
+        }
+        operands
+    };
+
+    match exp {
+        Expression::Operation(Operation::NOT(Not(inner))) => Expression::Operation(Operation::NOT(
+            Not(Box::new(replace_at(inner, rest, new.clone()))),
+        )),
+        Expression::Operation(Operation::AND(And(operands))) => {
+            And::new(replace_operand(operands, |exp| {
+                And::try_from_expression(exp).map(And::into_operands)
+            }))
+            .into_expression()
+        }
+        Expression::Operation(Operation::OR(Or(operands))) => {
+            Or::new(replace_operand(operands, |exp| {
+                Or::try_from_expression(exp).map(Or::into_operands)
+            }))
+            .into_expression()
+        }
+        Expression::Operation(Operation::XOR(Xor(operands))) => {
+            Xor::new(replace_operand(operands, |exp| {
+                Xor::try_from_expression(exp).map(Xor::into_operands)
+            }))
+            .into_expression()
+        }
+        _ => unreachable!("Only operations have operands"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::grammar::parse;
+
+    fn rewritten(source: &str, strategy: Strategy) -> Expression {
+        Rewriter::standard()
+            .rewrite(&parse(source).unwrap(), strategy)
+            .unwrap()
+    }
+
+    #[test]
+    fn strategies_reach_a_normal_form() {
+        for strategy in [
+            Strategy::Innermost,
+            Strategy::Outermost,
+            Strategy::Fixpoint(size),
+        ] {
+            assert_eq!(
+                rewritten("(a | b) & (a | ~b)", strategy),
+                parse("a").unwrap()
+            );
+            assert_eq!(rewritten("~~(x ^ x) | c", strategy), parse("c").unwrap());
+        }
+        assert_eq!(
+            rewritten("~(a & (b | c))", Strategy::Innermost).canonicalize(),
+            parse("~a | ~b & ~c").unwrap().canonicalize()
+        );
+    }
+
+    #[test]
+    fn innermost_and_outermost_order() {
+        let exp = parse("~(~(a | b) & 1)").unwrap();
+        let rewriter = Rewriter::standard();
+
+        let (law, position, _) = rewriter.next_rewrite(&exp, Strategy::Innermost).unwrap();
+        assert_eq!((law.name(), position), ("De Morgan", vec![0, 0]));
+        let (law, position, _) = rewriter.next_rewrite(&exp, Strategy::Outermost).unwrap();
+        assert_eq!((law.name(), position), ("De Morgan", vec![]));
+    }
+
+    #[test]
+    fn cost_never_grows() {
+        let exp = parse("~(a & b) | a & (b | c)").unwrap();
+        assert!(
+            size(&rewritten(
+                "~(a & b) | a & (b | c)",
+                Strategy::Fixpoint(size)
+            )) <= size(&exp)
+        );
+    }
+
+    #[test]
+    fn disabling_laws() {
+        let mut rewriter = Rewriter::standard();
+        rewriter.disable("De Morgan").unwrap();
+        let exp = parse("~(a | b)").unwrap();
+        assert_eq!(rewriter.rewrite(&exp, Strategy::Innermost).unwrap(), exp);
+
+        rewriter.enable("De Morgan").unwrap();
+        assert_eq!(
+            rewriter.rewrite(&exp, Strategy::Innermost).unwrap(),
+            parse("~a ~b").unwrap()
+        );
+        assert!(rewriter.disable("Magic").is_err());
+    }
+
+    #[test]
+    fn steps() {
+        let exp = parse("~(~(a | b) & 1)").unwrap();
+        let steps = Rewriter::standard()
+            .rewrite_steps(&exp, Strategy::Innermost)
+            .unwrap();
+
+        assert_eq!(steps[0].before, exp);
+        assert_eq!(
+            (steps[0].law.as_str(), steps[0].position.clone()),
+            ("De Morgan", vec![0, 0])
+        );
+        assert!(steps.windows(2).all(|pair| pair[0].after == pair[1].before));
+        assert_eq!(steps.last().unwrap().after, parse("a | b").unwrap());
+        assert!(Rewriter::standard()
+            .rewrite_steps(&parse("a b").unwrap(), Strategy::Innermost)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn custom_laws() {
+        assert!(Law::new("Broken", "A & B", "C").is_err());
+
+        let mut rewriter = Rewriter::new(vec![]);
+        rewriter.add_law(Law::new("Commutativity", "A & B", "B & A").unwrap());
+        rewriter.set_max_steps(10);
+        assert!(rewriter
+            .rewrite(&parse("a & (b | c)").unwrap(), Strategy::Innermost)
+            .is_err());
+    }
+}
@@ -0,0 +1,490 @@
+use std::collections::HashMap;
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    parser::{scanner::Scanner, tokenizer::Token, tokenizer::Tokenizer},
+    term::Term,
+    traits::VariableLengthOperator,
+    var::{Var, VarName},
+};
+
+/// What the metavariables of a pattern stand for
+pub type Bindings = HashMap<VarName, Expression>;
+
+/// Left or right side of a law. Uppercase names are metavariables that match any
+/// subexpression, lowercase names only match that variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    Meta(VarName),
+    Constant(Constant),
+    Var(VarName),
+    Not(Box<Pattern>),
+    And(Vec<Pattern>),
+    Or(Vec<Pattern>),
+    Xor(Vec<Pattern>),
+}
+
+/// A match of a pattern at the top of an expression. Operands of the expression the pattern
+/// didn't need are left in `rest`, the replacement goes in at `index`, where the first of
+/// the matched operands was.
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub bindings: Bindings,
+    pub rest: Vec<Expression>,
+    pub index: usize,
+}
+
+/// Which operator an operand list belongs to, so matching doesn't depend on the operator type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    And,
+    Or,
+    Xor,
+}
+
+impl Pattern {
+    /// Same syntax as expressions, but `~` is kept as written, so `~~A` is a pattern
+    pub fn parse(source: &str) -> Result<Pattern, String> {
+        let mut scanner = Scanner::new(source);
+        let lexemes = scanner.scan();
+        if scanner.has_errors() {
+            return Err(scanner.get_errors().join("\n"));
+        }
+        let mut tokenizer = Tokenizer::new();
+        let tokens = tokenizer.tokenize(lexemes, source);
+        if tokenizer.has_errors() {
+            return Err(tokenizer.get_errors().join("\n"));
+        }
+
+        let mut parser = PatternParser { tokens, pos: 0 };
+        let pattern = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(pattern),
+            Some(token) => Err(format!("Unexpected token '{:?}' in pattern!", token)),
+        }
+    }
+
+    pub fn metavariables(&self, names: &mut Vec<VarName>) {
+        match self {
+            Pattern::Meta(name) if !names.contains(name) => names.push(*name),
+            Pattern::Meta(_) | Pattern::Constant(_) | Pattern::Var(_) => (),
+            Pattern::Not(inner) => inner.metavariables(names),
+            Pattern::And(patterns) | Pattern::Or(patterns) | Pattern::Xor(patterns) => {
+                patterns.iter().for_each(|p| p.metavariables(names))
+            }
+        }
+    }
+
+    /// Match the whole expression, operands of AND, OR and XOR in any order. When an
+    /// operation has more operands than the pattern, one metavariable operand takes the extra
+    /// ones, so `A & B` matches `a & b & c` with B = b & c.
+    pub fn matches(&self, exp: &Expression, bindings: &mut Bindings) -> bool {
+        match self {
+            Pattern::Meta(name) => match bindings.get(name) {
+                Some(bound) => same(bound, exp),
+                None => {
+                    bindings.insert(*name, exp.clone());
+                    true
+                }
+            },
+            Pattern::Constant(constant) => *exp == Expression::Constant(*constant),
+            Pattern::Var(name) => {
+                matches!(exp, Expression::Var(var) if var.name() == *name && !var.is_negated())
+            }
+            Pattern::Not(inner) => match negated_operand(exp) {
+                Some(operand) => inner.matches(&operand, bindings),
+                None => false,
+            },
+            Pattern::And(patterns) => self.matches_operands(Kind::And, patterns, exp, bindings),
+            Pattern::Or(patterns) => self.matches_operands(Kind::Or, patterns, exp, bindings),
+            Pattern::Xor(patterns) => self.matches_operands(Kind::Xor, patterns, exp, bindings),
+        }
+    }
+
+    fn matches_operands(
+        &self,
+        kind: Kind,
+        patterns: &[Pattern],
+        exp: &Expression,
+        bindings: &mut Bindings,
+    ) -> bool {
+        let Some(operands) = operands_of(kind, exp) else {
+            return false;
+        };
+        if operands.len() < patterns.len() {
+            return false;
+        }
+
+        let found = if operands.len() == patterns.len() {
+            assign(patterns, &operands, vec![false; operands.len()], bindings)
+                .map(|(found, _)| found)
+        } else {
+            collect_extra(kind, patterns, &operands, bindings)
+        };
+        match found {
+            Some(found) => {
+                *bindings = found;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Like `matches`, but at the top operands the pattern doesn't use are returned as the rest
+    /// instead of going into a metavariable
+    pub fn match_top(&self, exp: &Expression) -> Option<Match> {
+        let Some((kind, patterns)) = self.operation() else {
+            let mut bindings = Bindings::new();
+            return self.matches(exp, &mut bindings).then_some(Match {
+                bindings,
+                rest: vec![],
+                index: 0,
+            });
+        };
+
+        let operands = operands_of(kind, exp)?;
+        if operands.len() < patterns.len() {
+            return None;
+        }
+        let (bindings, used) = assign(
+            patterns,
+            &operands,
+            vec![false; operands.len()],
+            &Bindings::new(),
+        )?;
+        let index = used.iter().position(|used| *used).unwrap_or(0);
+        let rest = operands
+            .into_iter()
+            .zip(used)
+            .filter_map(|(exp, used)| (!used).then_some(exp))
+            .collect();
+        Some(Match {
+            bindings,
+            rest,
+            index,
+        })
+    }
+
+    fn operation(&self) -> Option<(Kind, &[Pattern])> {
+        match self {
+            Pattern::And(patterns) => Some((Kind::And, patterns)),
+            Pattern::Or(patterns) => Some((Kind::Or, patterns)),
+            Pattern::Xor(patterns) => Some((Kind::Xor, patterns)),
+            _ => None,
+        }
+    }
+
+    /// Build the expression with every metavariable replaced by what it is bound to
+    pub fn instantiate(&self, bindings: &Bindings) -> Expression {
+        match self {
+            Pattern::Meta(name) => bindings[name].clone(),
+            Pattern::Constant(constant) => Expression::Constant(*constant),
+            Pattern::Var(name) => Expression::Var(Var::new_from_name(*name, false)),
+            Pattern::Not(inner) => negate(inner.instantiate(bindings)),
+            Pattern::And(patterns) => build(Kind::And, instantiate_all(patterns, bindings)),
+            Pattern::Or(patterns) => build(Kind::Or, instantiate_all(patterns, bindings)),
+            Pattern::Xor(patterns) => build(Kind::Xor, instantiate_all(patterns, bindings)),
+        }
+    }
+
+    /// Replacement for the top of an expression the left side matched with `found`
+    pub fn replace(&self, lhs: &Pattern, found: Match) -> Expression {
+        let replacement = self.instantiate(&found.bindings);
+        if found.rest.is_empty() {
+            return replacement;
+        }
+
+        let Some((kind, _)) = lhs.operation() else {
+            unreachable!("Only operations leave operands over")
+        };
+        let mut operands = found.rest;
+        operands.insert(found.index, replacement);
+        build(kind, operands)
+    }
+}
+
+fn instantiate_all(patterns: &[Pattern], bindings: &Bindings) -> Vec<Expression> {
+    patterns.iter().map(|p| p.instantiate(bindings)).collect()
+}
+
+/// Equal up to the order of operands
+fn same(left: &Expression, right: &Expression) -> bool {
+    left == right || left.clone().canonicalize() == right.clone().canonicalize()
+}
+
+/// The operand of a negation, negated variables count too
+fn negated_operand(exp: &Expression) -> Option<Expression> {
+    match exp {
+        Expression::Operation(Operation::NOT(Not(inner))) => Some(*inner.clone()),
+        Expression::Var(var) if var.is_negated() => Some(Expression::Var(var.negate())),
+        _ => None,
+    }
+}
+
+/// Operands of the operation of `kind` at the top of `exp`, terms count as AND
+fn operands_of(kind: Kind, exp: &Expression) -> Option<Vec<Expression>> {
+    match (kind, exp) {
+        (Kind::And, Expression::Term(term)) => Some(
+            term.vars()
+                .iter()
+                .map(|var| Expression::Var(*var))
+                .collect(),
+        ),
+        (Kind::And, Expression::Operation(Operation::AND(And(operands))))
+        | (Kind::Or, Expression::Operation(Operation::OR(Or(operands))))
+        | (Kind::Xor, Expression::Operation(Operation::XOR(Xor(operands)))) => {
+            Some(operands.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Give every pattern its own operand, trying every order until the bindings agree
+fn assign(
+    patterns: &[Pattern],
+    operands: &[Expression],
+    used: Vec<bool>,
+    bindings: &Bindings,
+) -> Option<(Bindings, Vec<bool>)> {
+    let Some((first, others)) = patterns.split_first() else {
+        return Some((bindings.clone(), used));
+    };
+
+    for i in (0..operands.len()).filter(|i| !used[*i]) {
+        let mut tried = bindings.clone();
+        if first.matches(&operands[i], &mut tried) {
+            let mut used = used.clone();
+            used[i] = true;
+            if let Some(found) = assign(others, operands, used, &tried) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Match all but one metavariable operand one to one, the left over operands go into that one
+fn collect_extra(
+    kind: Kind,
+    patterns: &[Pattern],
+    operands: &[Expression],
+    bindings: &Bindings,
+) -> Option<Bindings> {
+    (0..patterns.len())
+        .filter(|m| matches!(patterns[*m], Pattern::Meta(_)))
+        .find_map(|m| {
+            let others: Vec<Pattern> = patterns
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != m)
+                .map(|(_, p)| p.clone())
+                .collect();
+            let (mut found, used) =
+                assign(&others, operands, vec![false; operands.len()], bindings)?;
+
+            let extra: Vec<Expression> = operands
+                .iter()
+                .zip(used)
+                .filter(|(_, used)| !used)
+                .map(|(exp, _)| exp.clone())
+                .collect();
+            patterns[m]
+                .matches(&build(kind, extra), &mut found)
+                .then_some(found)
+        })
+}
+
+/// Negation, written the way the parser would build it
+fn negate(exp: Expression) -> Expression {
+    match exp {
+        Expression::Var(var) => Expression::Var(var.negate()),
+        other => Expression::Operation(Operation::NOT(Not(Box::new(other)))),
+    }
+}
+
+/// Operation of `kind` over `operands`, nested operations of the same kind get flattened and
+/// an AND of variables becomes a term, like the parser builds it
+fn build(kind: Kind, operands: Vec<Expression>) -> Expression {
+    let mut flat: Vec<Expression> = vec![];
+    for exp in operands.into_iter() {
+        match operands_of(kind, &exp) {
+            Some(mut inner) => flat.append(&mut inner),
+            None => flat.push(exp),
+        }
+    }
+
+    if flat.len() == 1 {
+        return flat.pop().unwrap();
+    }
+    match kind {
+        Kind::And if flat.iter().all(|exp| matches!(exp, Expression::Var(_))) => {
+            let vars = flat
+                .into_iter()
+                .filter_map(|exp| match exp {
+                    Expression::Var(var) => Some(var),
+                    _ => None,
+                })
+                .collect();
+            Expression::Term(Term::new_from_vars(vars))
+        }
+        Kind::And => And::new(flat).into_expression(),
+        Kind::Or => Or::new(flat).into_expression(),
+        Kind::Xor => Xor::new(flat).into_expression(),
+    }
+}
+
+struct PatternParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl PatternParser {
+    fn next_if(&mut self, predicate: fn(&Token) -> bool) -> bool {
+        if self.tokens.get(self.pos).is_some_and(predicate) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Pattern, String> {
+        let mut patterns = vec![self.xor()?];
+        while self.next_if(|t| matches!(t, Token::Pipe)) {
+            patterns.push(self.xor()?);
+        }
+        Ok(Self::collect(patterns, Pattern::Or))
+    }
+
+    fn xor(&mut self) -> Result<Pattern, String> {
+        let mut patterns = vec![self.and()?];
+        while self.next_if(|t| matches!(t, Token::Caret)) {
+            patterns.push(self.and()?);
+        }
+        Ok(Self::collect(patterns, Pattern::Xor))
+    }
+
+    fn and(&mut self) -> Result<Pattern, String> {
+        let mut patterns = vec![self.unary()?];
+        loop {
+            self.next_if(|t| matches!(t, Token::Ampersand));
+            match self.tokens.get(self.pos) {
+                Some(Token::Tilde | Token::C0 | Token::C1 | Token::Identifier(_))
+                | Some(Token::ParenL | Token::BracketL) => patterns.push(self.unary()?),
+                _ => break,
+            }
+        }
+        Ok(Self::collect(patterns, Pattern::And))
+    }
+
+    fn unary(&mut self) -> Result<Pattern, String> {
+        if self.next_if(|t| matches!(t, Token::Tilde)) {
+            return Ok(Pattern::Not(Box::new(self.unary()?)));
+        }
+
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::C0) => Ok(Pattern::Constant(Constant::ZERO)),
+            Some(Token::C1) => Ok(Pattern::Constant(Constant::ONE)),
+            Some(Token::Identifier(name)) if name.identifier().starts_with(char::is_uppercase) => {
+                Ok(Pattern::Meta(name))
+            }
+            Some(Token::Identifier(name)) => Ok(Pattern::Var(name)),
+            Some(open @ (Token::ParenL | Token::BracketL)) => {
+                let pattern = self.or()?;
+                let closing = match open {
+                    Token::ParenL => Token::ParenR,
+                    _ => Token::BracketR,
+                };
+                if self.tokens.get(self.pos) != Some(&closing) {
+                    return Err(format!("Missing closing '{:?}' in pattern!", closing));
+                }
+                self.pos += 1;
+                Ok(pattern)
+            }
+            Some(other) => Err(format!("Unexpected token '{:?}' in pattern!", other)),
+            None => Err(String::from("Unexpected end of pattern!")),
+        }
+    }
+
+    fn collect(mut patterns: Vec<Pattern>, op: fn(Vec<Pattern>) -> Pattern) -> Pattern {
+        if patterns.len() == 1 {
+            patterns.pop().unwrap()
+        } else {
+            op(patterns)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::grammar::parse;
+
+    #[test]
+    fn parses_negations_as_written() {
+        let a = VarName::new('A', None);
+        assert_eq!(
+            Pattern::parse("~~A").unwrap(),
+            Pattern::Not(Box::new(Pattern::Not(Box::new(Pattern::Meta(a)))))
+        );
+        assert_eq!(
+            Pattern::parse("A x & 1").unwrap(),
+            Pattern::And(vec![
+                Pattern::Meta(a),
+                Pattern::Var(VarName::new('x', None)),
+                Pattern::Constant(Constant::ONE)
+            ])
+        );
+    }
+
+    #[test]
+    fn matches_in_any_order() {
+        let pattern = Pattern::parse("A | A & B").unwrap();
+        let found = pattern
+            .match_top(&parse("c | (x ^ y) & (a | b) | (a | b)").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            found.bindings[&VarName::new('A', None)],
+            parse("a | b").unwrap()
+        );
+        assert_eq!(
+            found.bindings[&VarName::new('B', None)],
+            parse("x ^ y").unwrap()
+        );
+        assert_eq!(found.rest, vec![parse("c").unwrap()]);
+        assert_eq!(found.index, 1);
+        assert_eq!(
+            Pattern::parse("A").unwrap().replace(&pattern, found),
+            parse("c | a | b").unwrap()
+        );
+    }
+
+    #[test]
+    fn metavariable_takes_extra_operands() {
+        let pattern = Pattern::parse("~(A & B)").unwrap();
+        let found = pattern.match_top(&parse("~(a b c)").unwrap()).unwrap();
+        let rhs = Pattern::parse("~A | ~B").unwrap();
+        assert_eq!(
+            rhs.replace(&pattern, found).canonicalize(),
+            parse("~a | ~(b c)").unwrap().canonicalize()
+        );
+    }
+
+    #[test]
+    fn repeated_metavariables_must_agree() {
+        let pattern = Pattern::parse("A & ~A").unwrap();
+        assert!(pattern
+            .match_top(&parse("(a | b) & ~(b | a)").unwrap())
+            .is_some());
+        assert!(pattern
+            .match_top(&parse("(a | b) & ~(b | c)").unwrap())
+            .is_none());
+        assert!(pattern.match_top(&parse("x1 ~x1 y").unwrap()).is_some());
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{scope::VarPatterns, var::VarName};
+
+/// Pseudo random patterns for every name, 64 random assignments at once for simulation.
+/// `state` is the seed and gets advanced.
+pub fn random_patterns(names: &[VarName], state: &mut u64) -> VarPatterns<'static> {
+    names
+        .iter()
+        .map(|name| {
+            // xorshift64*, good enough to find differences
+            *state ^= *state >> 12;
+            *state ^= *state << 25;
+            *state ^= *state >> 27;
+            (*name, state.wrapping_mul(0x2545_F491_4F6C_DD1D))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::grammar::parse,
+        traits::{EvalBits, VarVisibility},
+    };
+
+    #[test]
+    fn simulation_finds_differences() {
+        let names = parse("a b c").unwrap().get_sorted_variables();
+        let mut state = 0x51;
+        let patterns = random_patterns(&names, &mut state);
+
+        let left = parse("a & (b | c)").unwrap();
+        let right = parse("a b | a c").unwrap();
+        let wrong = parse("a b | c").unwrap();
+        assert_eq!(
+            left.evaluate_bits(&patterns),
+            right.evaluate_bits(&patterns)
+        );
+        assert_ne!(
+            left.evaluate_bits(&patterns),
+            wrong.evaluate_bits(&patterns)
+        );
+    }
+}
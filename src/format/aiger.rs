@@ -0,0 +1,650 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    format::hdl::{Language, PortNames, PortStyle},
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    traits::VarVisibility,
+    var::{Var, VarName},
+};
+
+/// A combinational and-inverter graph in AIGER numbering. Literal `2v` is variable `v`, `2v + 1`
+/// its negation, variable 0 is constant false, inputs come first and AND gates after them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aig {
+    pub inputs: Vec<VarName>,
+    /// fanins of AND gate `i`, which is variable `inputs.len() + 1 + i`
+    pub ands: Vec<(u32, u32)>,
+    pub outputs: Vec<(String, u32)>,
+}
+
+impl Aig {
+    pub fn from_expression(exp: &Expression, output_name: &str) -> Aig {
+        let inputs = exp.get_sorted_variables();
+
+        let mut builder = AigBuilder {
+            input_literals: inputs
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (*name, 2 * (i as u32 + 1)))
+                .collect(),
+            first_and: inputs.len() as u32 + 1,
+            ands: vec![],
+            known: HashMap::new(),
+        };
+        let output = builder.lower(exp);
+
+        Aig {
+            inputs,
+            ands: builder.ands,
+            outputs: vec![(output_name.to_string(), output)],
+        }
+    }
+
+    fn max_variable(&self) -> usize {
+        self.inputs.len() + self.ands.len()
+    }
+
+    fn write_symbols(&self, out: &mut String) {
+        let ports = PortNames::new(&self.inputs, PortStyle::Plain, Language::Netlist);
+        for (i, port) in ports.inputs().iter().enumerate() {
+            *out += &format!("i{} {}\n", i, port);
+        }
+        for (i, (name, _)) in self.outputs.iter().enumerate() {
+            *out += &format!("o{} {}\n", i, name);
+        }
+    }
+
+    pub fn to_ascii(&self) -> String {
+        let mut out = format!(
+            "aag {} {} 0 {} {}\n",
+            self.max_variable(),
+            self.inputs.len(),
+            self.outputs.len(),
+            self.ands.len()
+        );
+        for i in 0..self.inputs.len() {
+            out += &format!("{}\n", 2 * (i + 1));
+        }
+        for (_, literal) in self.outputs.iter() {
+            out += &format!("{}\n", literal);
+        }
+        for (i, (rhs0, rhs1)) in self.ands.iter().enumerate() {
+            let lhs = 2 * (self.inputs.len() + 1 + i);
+            out += &format!("{} {} {}\n", lhs, rhs0, rhs1);
+        }
+        self.write_symbols(&mut out);
+        out
+    }
+
+    /// Binary AIGER stores fanins as deltas, which needs `lhs > rhs0 >= rhs1` for every gate
+    pub fn to_binary(&self) -> Result<Vec<u8>, String> {
+        let mut out = format!(
+            "aig {} {} 0 {} {}\n",
+            self.max_variable(),
+            self.inputs.len(),
+            self.outputs.len(),
+            self.ands.len()
+        );
+        for (_, literal) in self.outputs.iter() {
+            out += &format!("{}\n", literal);
+        }
+
+        let mut bytes = out.into_bytes();
+        for (i, (a, b)) in self.ands.iter().enumerate() {
+            let lhs = 2 * (self.inputs.len() as u32 + 1 + i as u32);
+            let (rhs0, rhs1) = ((*a).max(*b), (*a).min(*b));
+            if lhs <= rhs0 {
+                return Err(format!("AND gate {} uses a later gate!", lhs));
+            }
+            encode_delta(&mut bytes, lhs - rhs0);
+            encode_delta(&mut bytes, rhs0 - rhs1);
+        }
+
+        let mut symbols = String::new();
+        self.write_symbols(&mut symbols);
+        bytes.extend(symbols.into_bytes());
+        Ok(bytes)
+    }
+
+    /// Parses both the ASCII (`aag`) and the binary (`aig`) format
+    pub fn parse(source: &[u8]) -> Result<Aig, String> {
+        let mut reader = Reader { source, pos: 0 };
+        let header = reader.line()?;
+        let fields: Vec<&str> = header.split_whitespace().collect();
+
+        let (binary, counts) = match fields.as_slice() {
+            ["aag", counts @ ..] => (false, counts),
+            ["aig", counts @ ..] => (true, counts),
+            _ => return Err(format!("'{}' is not an AIGER header!", header)),
+        };
+        let counts: Vec<u32> = counts
+            .iter()
+            .map(|c| {
+                c.parse::<u32>()
+                    .map_err(|_| format!("Invalid header '{}'!", header))
+            })
+            .collect::<Result<_, _>>()?;
+        // AIGER 1.9 adds bad state, constraint, justice and fairness counts
+        let [max_variable, inputs, latches, outputs, ands, ref properties @ ..] = counts[..] else {
+            return Err(format!("Header '{}' needs 5 to 9 numbers!", header));
+        };
+        if properties.len() > 4 {
+            return Err(format!("Header '{}' needs 5 to 9 numbers!", header));
+        }
+        if latches > 0 {
+            return Err(String::from(
+                "Latches are not supported, only combinational AIGs are!",
+            ));
+        }
+        if properties.iter().any(|count| *count > 0) {
+            return Err(String::from(
+                "Bad states, constraints, justice and fairness properties are not supported!",
+            ));
+        }
+
+        let (output_literals, aig_ands) = if binary {
+            Self::read_binary_gates(&mut reader, &header, max_variable, inputs, outputs, ands)?
+        } else {
+            Self::read_ascii_gates(&mut reader, max_variable, inputs, outputs, ands)?
+        };
+
+        let mut input_names: Vec<Option<VarName>> = vec![None; inputs as usize];
+        let mut output_names: Vec<String> = (0..outputs).map(|i| format!("o{}", i)).collect();
+        while let Ok(line) = reader.line() {
+            if line == "c" {
+                break;
+            }
+            let Some((position, name)) = line.split_once(' ') else {
+                continue;
+            };
+            let mut chars = position.chars();
+            let kind = chars.next();
+            let index: Option<usize> = chars.as_str().parse().ok();
+            match (kind, index) {
+                (Some('i'), Some(i)) if i < input_names.len() => {
+                    input_names[i] = Some(VarName::new_from_identifier(name))
+                }
+                (Some('o'), Some(i)) if i < output_names.len() => {
+                    output_names[i] = name.to_string()
+                }
+                (Some(kind), _) if kind.is_ascii_alphabetic() => {}
+                _ => return Err(format!("Invalid symbol '{}'!", line)),
+            }
+        }
+
+        let numbered = VarName::numbered(input_names.len());
+        Ok(Aig {
+            inputs: input_names
+                .into_iter()
+                .zip(numbered)
+                .map(|(name, default)| name.unwrap_or(default))
+                .collect(),
+            ands: aig_ands,
+            outputs: output_names.into_iter().zip(output_literals).collect(),
+        })
+    }
+
+    /// The binary format is always reencoded: inputs are the variables right after 0 and every
+    /// gate only uses earlier ones, so only the deltas of the fanins are stored
+    fn read_binary_gates(
+        reader: &mut Reader,
+        header: &str,
+        max_variable: u32,
+        inputs: u32,
+        outputs: u32,
+        ands: u32,
+    ) -> Result<Gates, String> {
+        if max_variable != inputs + ands {
+            return Err(format!("Header '{}' isn't in reencoded form!", header));
+        }
+
+        let mut output_literals = vec![];
+        for _ in 0..outputs {
+            output_literals.push(reader.number_line()?);
+        }
+
+        let mut aig_ands = vec![];
+        for i in 0..ands {
+            let lhs = 2 * (inputs + 1 + i);
+            let rhs0 = lhs
+                .checked_sub(reader.delta()?)
+                .ok_or("Invalid AND gate delta!")?;
+            let rhs1 = rhs0
+                .checked_sub(reader.delta()?)
+                .ok_or("Invalid AND gate delta!")?;
+            if rhs0 >= lhs {
+                return Err(format!("AND gate {} uses a later gate!", lhs));
+            }
+            aig_ands.push((rhs0, rhs1));
+        }
+        Ok((output_literals, aig_ands))
+    }
+
+    /// ASCII files may use any variable up to the maximum, in any order. They get renumbered
+    /// the way the binary format numbers them, every gate after the gates it uses.
+    fn read_ascii_gates(
+        reader: &mut Reader,
+        max_variable: u32,
+        inputs: u32,
+        outputs: u32,
+        ands: u32,
+    ) -> Result<Gates, String> {
+        let defined = |literal: u32| {
+            if literal < 2 || literal % 2 == 1 || literal / 2 > max_variable {
+                return Err(format!(
+                    "{} can't be defined as an input or a gate!",
+                    literal
+                ));
+            }
+            Ok(literal / 2)
+        };
+
+        // variable in the file -> variable in the reencoded graph
+        let mut variables: HashMap<u32, u32> = HashMap::new();
+        for i in 0..inputs {
+            let variable = defined(reader.number_line()?)?;
+            if variables.insert(variable, i + 1).is_some() {
+                return Err(format!("Variable {} is defined twice!", variable));
+            }
+        }
+
+        let mut output_literals = vec![];
+        for _ in 0..outputs {
+            output_literals.push(reader.number_line()?);
+        }
+
+        let mut gates: HashMap<u32, (u32, u32)> = HashMap::new();
+        let mut order = vec![];
+        for _ in 0..ands {
+            let line = reader.line()?;
+            let literals: Vec<u32> = line
+                .split_whitespace()
+                .map(|l| {
+                    l.parse::<u32>()
+                        .map_err(|_| format!("Invalid AND '{}'!", line))
+                })
+                .collect::<Result<_, _>>()?;
+            let [lhs, rhs0, rhs1] = literals[..] else {
+                return Err(format!("Invalid AND '{}'!", line));
+            };
+            let variable = defined(lhs)?;
+            if variables.contains_key(&variable) || gates.insert(variable, (rhs0, rhs1)).is_some() {
+                return Err(format!("Variable {} is defined twice!", variable));
+            }
+            order.push(variable);
+        }
+
+        let renumber = |variables: &HashMap<u32, u32>, literal: u32| {
+            if literal < 2 {
+                return Ok(literal);
+            }
+            variables
+                .get(&(literal / 2))
+                .map(|variable| (2 * variable) | (literal % 2))
+                .ok_or_else(|| format!("Literal {} is never defined!", literal))
+        };
+
+        // depth first without recursion, deep graphs are common
+        let mut aig_ands = vec![];
+        let mut visiting: HashSet<u32> = HashSet::new();
+        for root in order {
+            let mut stack = vec![(root, false)];
+            while let Some((variable, fanins_done)) = stack.pop() {
+                if variables.contains_key(&variable) {
+                    continue;
+                }
+                let (a, b) = gates[&variable];
+                if fanins_done {
+                    let (a, b) = (renumber(&variables, a)?, renumber(&variables, b)?);
+                    // the binary format needs rhs0 >= rhs1, keep both in the same order
+                    aig_ands.push((a.max(b), a.min(b)));
+                    variables.insert(variable, inputs + aig_ands.len() as u32);
+                    visiting.remove(&variable);
+                    continue;
+                }
+                if !visiting.insert(variable) {
+                    return Err(format!("AND gate {} depends on itself!", 2 * variable));
+                }
+                stack.push((variable, true));
+                for fanin in [a / 2, b / 2] {
+                    if gates.contains_key(&fanin) && !variables.contains_key(&fanin) {
+                        stack.push((fanin, false));
+                    }
+                }
+            }
+        }
+
+        let output_literals = output_literals
+            .into_iter()
+            .map(|literal| renumber(&variables, literal))
+            .collect::<Result<_, _>>()?;
+        Ok((output_literals, aig_ands))
+    }
+
+    pub fn to_expression(&self, output: usize) -> Result<Expression, String> {
+        let (_, literal) = self
+            .outputs
+            .get(output)
+            .ok_or_else(|| format!("There is no output #{}!", output))?;
+        let max_literal = 2 * self.max_variable() as u32 + 1;
+        if *literal > max_literal
+            || self
+                .ands
+                .iter()
+                .any(|(a, b)| *a > max_literal || *b > max_literal)
+        {
+            return Err(String::from("Literal out of range!"));
+        }
+        Ok(self.literal_to_expression(*literal, &mut HashMap::new()))
+    }
+
+    fn literal_to_expression(
+        &self,
+        literal: u32,
+        cache: &mut HashMap<u32, Expression>,
+    ) -> Expression {
+        let variable = (literal / 2) as usize;
+        let negated = literal % 2 == 1;
+
+        if variable == 0 {
+            return Expression::Constant(Constant::ZERO.negate_if(negated));
+        }
+        if variable <= self.inputs.len() {
+            let name = self.inputs[variable - 1];
+            return Expression::Var(Var::new_from_name(name, negated));
+        }
+
+        let positive = match cache.get(&(literal & !1)) {
+            Some(exp) => exp.clone(),
+            None => {
+                let (rhs0, rhs1) = self.ands[variable - self.inputs.len() - 1];
+                let exp = Expression::Operation(Operation::AND(And(vec![
+                    self.literal_to_expression(rhs0, cache),
+                    self.literal_to_expression(rhs1, cache),
+                ])));
+                cache.insert(literal & !1, exp.clone());
+                exp
+            }
+        };
+
+        if negated {
+            Expression::Operation(Operation::NOT(Not(Box::new(positive))))
+        } else {
+            positive
+        }
+    }
+}
+
+/// The output literals and the fanins of every AND gate, as read from a file
+type Gates = (Vec<u32>, Vec<(u32, u32)>);
+
+struct AigBuilder {
+    input_literals: HashMap<VarName, u32>,
+    first_and: u32,
+    ands: Vec<(u32, u32)>,
+    /// structural hashing, identical gates are only created once
+    known: HashMap<(u32, u32), u32>,
+}
+
+impl AigBuilder {
+    fn and(&mut self, a: u32, b: u32) -> u32 {
+        let (rhs0, rhs1) = if a >= b { (a, b) } else { (b, a) };
+        if rhs1 == 0 {
+            return 0;
+        }
+        if rhs1 == 1 || rhs0 == rhs1 {
+            return rhs0;
+        }
+        if rhs0 == rhs1 ^ 1 {
+            return 0;
+        }
+        if let Some(literal) = self.known.get(&(rhs0, rhs1)) {
+            return *literal;
+        }
+
+        let literal = 2 * (self.first_and + self.ands.len() as u32);
+        self.ands.push((rhs0, rhs1));
+        self.known.insert((rhs0, rhs1), literal);
+        literal
+    }
+
+    fn or(&mut self, a: u32, b: u32) -> u32 {
+        self.and(a ^ 1, b ^ 1) ^ 1
+    }
+
+    fn xor(&mut self, a: u32, b: u32) -> u32 {
+        let left = self.and(a, b ^ 1);
+        let right = self.and(a ^ 1, b);
+        self.or(left, right)
+    }
+
+    fn fold(
+        &mut self,
+        literals: Vec<u32>,
+        neutral: u32,
+        op: fn(&mut Self, u32, u32) -> u32,
+    ) -> u32 {
+        literals
+            .into_iter()
+            .reduce(|acc, literal| op(self, acc, literal))
+            .unwrap_or(neutral)
+    }
+
+    fn lower(&mut self, exp: &Expression) -> u32 {
+        match exp {
+            Expression::Constant(constant) => *constant as u32,
+            Expression::Var(var) => self.input_literals[&var.name()] ^ var.is_negated() as u32,
+            Expression::Term(term) => {
+                let literals = term
+                    .vars()
+                    .iter()
+                    .map(|var| self.input_literals[&var.name()] ^ var.is_negated() as u32)
+                    .collect();
+                self.fold(literals, 1, Self::and)
+            }
+            Expression::Operation(Operation::NOT(Not(inner))) => self.lower(inner) ^ 1,
+            Expression::Operation(Operation::AND(And(vecexp))) => {
+                let literals = vecexp.iter().map(|e| self.lower(e)).collect();
+                self.fold(literals, 1, Self::and)
+            }
+            Expression::Operation(Operation::OR(Or(vecexp))) => {
+                let literals = vecexp.iter().map(|e| self.lower(e)).collect();
+                self.fold(literals, 0, Self::or)
+            }
+            Expression::Operation(Operation::XOR(Xor(vecexp))) => {
+                let literals = vecexp.iter().map(|e| self.lower(e)).collect();
+                self.fold(literals, 0, Self::xor)
+            }
+        }
+    }
+}
+
+fn encode_delta(bytes: &mut Vec<u8>, mut delta: u32) {
+    while delta >= 0x80 {
+        bytes.push((delta & 0x7f) as u8 | 0x80);
+        delta >>= 7;
+    }
+    bytes.push(delta as u8);
+}
+
+struct Reader<'a> {
+    source: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn line(&mut self) -> Result<String, String> {
+        if self.pos >= self.source.len() {
+            return Err(String::from("Unexpected end of file!"));
+        }
+        let rest = &self.source[self.pos..];
+        let len = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+        self.pos += len + 1;
+        String::from_utf8(rest[..len].to_vec())
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .map_err(|_| String::from("Expected a text line!"))
+    }
+
+    fn number_line(&mut self) -> Result<u32, String> {
+        let line = self.line()?;
+        line.trim()
+            .parse::<u32>()
+            .map_err(|_| format!("'{}' is not a literal!", line))
+    }
+
+    fn delta(&mut self) -> Result<u32, String> {
+        let mut delta: u32 = 0;
+        for shift in (0..32).step_by(7) {
+            let byte = *self.source.get(self.pos).ok_or("Unexpected end of file!")?;
+            self.pos += 1;
+            delta |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(delta);
+            }
+        }
+        Err(String::from("AND gate delta is too long!"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{term::Term, testing::assert_equivalent};
+
+    fn example() -> Expression {
+        // ~(x1 x̄2) ^ (x1 | x3)
+        let x1 = Var::new('x', Some(1), false);
+        Expression::Operation(Operation::XOR(Xor(vec![
+            Expression::Operation(Operation::NOT(Not(Box::new(Expression::Term(
+                Term::new_from_vars(vec![x1, Var::new('x', Some(2), true)]),
+            ))))),
+            Expression::Operation(Operation::OR(Or(vec![
+                Expression::Var(x1),
+                Expression::Var(Var::new('x', Some(3), false)),
+            ]))),
+        ])))
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let aig = Aig::from_expression(&example(), "f");
+        let ascii = aig.to_ascii();
+        assert!(ascii.starts_with("aag 8 3 0 1 5\n2\n4\n6\n"));
+        assert!(ascii.ends_with("i0 x1\ni1 x2\ni2 x3\no0 f\n"));
+
+        let parsed = Aig::parse(ascii.as_bytes()).unwrap();
+        assert_eq!(parsed, aig);
+        assert_equivalent(&parsed.to_expression(0).unwrap(), &example());
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let aig = Aig::from_expression(&example(), "f");
+        let parsed = Aig::parse(&aig.to_binary().unwrap()).unwrap();
+        assert_eq!(parsed, aig);
+        assert_equivalent(&parsed.to_expression(0).unwrap(), &example());
+    }
+
+    #[test]
+    fn unsorted_fanins() {
+        let unsorted = Aig {
+            inputs: vec![VarName::new('a', None), VarName::new('b', None)],
+            ands: vec![(2, 5)],
+            outputs: vec![(String::from("f"), 6)],
+        };
+        let sorted = Aig {
+            ands: vec![(5, 2)],
+            ..unsorted.clone()
+        };
+
+        assert_eq!(Aig::parse(unsorted.to_ascii().as_bytes()).unwrap(), sorted);
+        assert_eq!(Aig::parse(&unsorted.to_binary().unwrap()).unwrap(), sorted);
+
+        let later = Aig {
+            ands: vec![(2, 8), (2, 4)],
+            ..unsorted
+        };
+        assert!(later.to_binary().is_err());
+    }
+
+    #[test]
+    fn structural_hashing() {
+        let a = Expression::Var(Var::new('a', None, false));
+        let b = Expression::Var(Var::new('b', None, false));
+        let and = Expression::Operation(Operation::AND(And(vec![a.clone(), b.clone()])));
+        let exp = Expression::Operation(Operation::OR(Or(vec![
+            and.clone(),
+            Expression::Operation(Operation::AND(And(vec![
+                b,
+                a.clone(),
+                Expression::Constant(Constant::ONE),
+            ]))),
+            Expression::Operation(Operation::AND(And(vec![
+                a.clone(),
+                Expression::Operation(Operation::NOT(Not(Box::new(a)))),
+            ]))),
+        ])));
+
+        // a & b is built once, a & ~a folds to 0
+        assert_eq!(Aig::from_expression(&exp, "f").ands.len(), 1);
+    }
+
+    #[test]
+    fn invalid_symbols() {
+        // an empty position and a multibyte first character
+        assert!(Aig::parse("aag 1 1 0 1 0\n2\n2\n a\n".as_bytes()).is_err());
+        assert!(Aig::parse("aag 1 1 0 1 0\n2\n2\né0 a\n".as_bytes()).is_err());
+
+        // other symbol kinds, like latches, are skipped
+        let aig = Aig::parse("aag 1 1 0 1 0\n2\n2\nl0 a\ni0 b\n".as_bytes()).unwrap();
+        assert_eq!(aig.inputs, vec![VarName::new('b', None)]);
+    }
+
+    #[test]
+    fn tool_generated_names() {
+        let aig = Aig::parse("aag 2 2 0 1 0\n2\n4\n2\ni0 a[0]\ni1 $abc$n3\n".as_bytes()).unwrap();
+        assert_eq!(
+            aig.inputs,
+            vec![
+                VarName::new_from_identifier("a[0]"),
+                VarName::new_from_identifier("$abc$n3")
+            ]
+        );
+    }
+
+    #[test]
+    fn ascii_renumbers_variables() {
+        // variable 3 is unused, the inputs are listed backwards and the gates out of order
+        let sparse = "aag 6 2 0 1 2\n4\n2\n13\n12 10 2\n10 2 4\n";
+        let aig = Aig::parse(sparse.as_bytes()).unwrap();
+        assert_eq!(
+            aig,
+            Aig {
+                inputs: VarName::numbered(2),
+                ands: vec![(4, 2), (6, 4)],
+                outputs: vec![(String::from("o0"), 9)],
+            }
+        );
+
+        assert!(Aig::parse(b"aag 3 1 0 1 2\n2\n4\n4 6 2\n6 4 2\n").is_err());
+        assert!(Aig::parse(b"aag 2 1 0 1 1\n2\n4\n4 2 6\n").is_err());
+        assert!(Aig::parse(b"aag 2 2 0 1 0\n2\n2\n2\n").is_err());
+    }
+
+    #[test]
+    fn version_1_9_headers() {
+        let aig = Aig::parse(b"aag 1 1 0 1 0 0 0 0 0\n2\n3\n").unwrap();
+        assert_eq!(aig.outputs, vec![(String::from("o0"), 3)]);
+
+        assert!(Aig::parse(b"aag 1 1 0 0 0 1\n2\n3\n").is_err());
+        assert!(Aig::parse(b"aag 1 1 0 1 0 0 0 0 0 0\n2\n3\n").is_err());
+    }
+
+    #[test]
+    fn rejects_latches() {
+        assert!(Aig::parse(b"aag 1 0 1 0 0\n2 3\n").is_err());
+    }
+}
@@ -0,0 +1,447 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    format::hdl::{Language, PortNames, PortStyle},
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    term::Term,
+    traits::VarVisibility,
+    var::{Var, VarName},
+};
+
+/// Lower the expression to a BLIF netlist of `.names` gates, identical gates are shared. Internal
+/// signals never reuse a port name, an output named like an input gets a suffix.
+pub fn to_blif(exp: &Expression, model_name: &str, output_name: &str) -> String {
+    let mut ports = PortNames::new(
+        &exp.get_sorted_variables(),
+        PortStyle::Plain,
+        Language::Netlist,
+    );
+    let output_name = ports.claim(output_name);
+
+    let mut lowering = Lowering {
+        ports,
+        ..Default::default()
+    };
+    lowering.lower(exp, Some(&output_name));
+
+    let mut out = format!(".model {}\n", model_name);
+    out += &format!(".inputs {}\n", lowering.ports.inputs().join(" "));
+    out += &format!(".outputs {}\n", output_name);
+
+    for gate in lowering.gates.iter() {
+        out += &format!(".names {}\n", gate.signals.join(" "));
+        for row in gate.rows.iter() {
+            out += row;
+            out += "\n";
+        }
+    }
+    out += ".end\n";
+    out
+}
+
+struct Gate {
+    /// inputs followed by the output
+    signals: Vec<String>,
+    rows: Vec<String>,
+}
+
+#[derive(Default)]
+struct Lowering {
+    gates: Vec<Gate>,
+    known: HashMap<(Vec<String>, Vec<String>), String>,
+    /// internal gates are named `n0`, `n1`, ... skipping the ports
+    ports: PortNames,
+    next: usize,
+}
+
+impl Lowering {
+    fn gate(&mut self, inputs: Vec<String>, rows: Vec<String>, name: Option<&str>) -> String {
+        let key = (inputs, rows);
+        if name.is_none() {
+            if let Some(signal) = self.known.get(&key) {
+                return signal.clone();
+            }
+        }
+
+        let signal = match name {
+            Some(name) => name.to_string(),
+            None => loop {
+                let name = format!("n{}", self.next);
+                self.next += 1;
+                if !self.ports.contains(&name) {
+                    break name;
+                }
+            },
+        };
+        let (mut signals, rows) = key.clone();
+        signals.push(signal.clone());
+
+        self.known.insert(key, signal.clone());
+        self.gates.push(Gate { signals, rows });
+        signal
+    }
+
+    /// Returns the signal carrying the value of `exp`, `name` forces a gate with that output
+    fn lower(&mut self, exp: &Expression, name: Option<&str>) -> String {
+        match exp {
+            Expression::Constant(Constant::ZERO) => self.gate(vec![], vec![], name),
+            Expression::Constant(Constant::ONE) => self.gate(vec![], vec!["1".into()], name),
+            Expression::Var(var) => {
+                let input = self.ports.get(&var.name()).to_string();
+                match (var.is_negated(), name) {
+                    (false, None) => input,
+                    (false, Some(_)) => self.gate(vec![input], vec!["1 1".into()], name),
+                    (true, _) => self.gate(vec![input], vec!["0 1".into()], name),
+                }
+            }
+            Expression::Term(term) => {
+                let inputs = term
+                    .vars()
+                    .iter()
+                    .map(|var| self.ports.get(&var.name()).to_string())
+                    .collect();
+                let mut row: String = term
+                    .vars()
+                    .iter()
+                    .map(|var| if var.is_negated() { '0' } else { '1' })
+                    .collect();
+                row += " 1";
+                self.gate(inputs, vec![row.trim_start().to_string()], name)
+            }
+            Expression::Operation(Operation::NOT(Not(inner))) => {
+                let input = self.lower(inner, None);
+                self.gate(vec![input], vec!["0 1".into()], name)
+            }
+            Expression::Operation(Operation::AND(And(vecexp))) => {
+                let inputs: Vec<String> = vecexp.iter().map(|e| self.lower(e, None)).collect();
+                let row = format!("{} 1", "1".repeat(inputs.len()));
+                self.gate(inputs, vec![row.trim_start().to_string()], name)
+            }
+            Expression::Operation(Operation::OR(Or(vecexp))) => {
+                let inputs: Vec<String> = vecexp.iter().map(|e| self.lower(e, None)).collect();
+                let rows = (0..inputs.len())
+                    .map(|i| {
+                        let mut row = vec!['-'; inputs.len()];
+                        row[i] = '1';
+                        format!("{} 1", row.into_iter().collect::<String>())
+                    })
+                    .collect();
+                self.gate(inputs, rows, name)
+            }
+            Expression::Operation(Operation::XOR(Xor(vecexp))) => {
+                // a chain of 2-input gates, a single cover would have 2^(n-1) rows
+                let inputs: Vec<String> = vecexp.iter().map(|e| self.lower(e, None)).collect();
+                match inputs.len() {
+                    0 => self.gate(vec![], vec![], name),
+                    1 => self.gate(inputs, vec!["1 1".into()], name),
+                    len => {
+                        let mut acc = inputs[0].clone();
+                        for (i, input) in inputs.iter().enumerate().skip(1) {
+                            let gate_name = if i == len - 1 { name } else { None };
+                            acc = self.gate(
+                                vec![acc, input.clone()],
+                                vec!["01 1".into(), "10 1".into()],
+                                gate_name,
+                            );
+                        }
+                        acc
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct Names {
+    inputs: Vec<String>,
+    rows: Vec<(String, char)>,
+}
+
+/// Reconstruct every output of a combinational BLIF model
+pub fn read_blif(source: &str) -> Result<Vec<(String, Expression)>, String> {
+    let mut inputs: HashSet<String> = HashSet::new();
+    let mut outputs: Vec<String> = vec![];
+    let mut gates: HashMap<String, Names> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    // a trailing backslash continues the line
+    let joined = source.replace("\\\r\n", " ").replace("\\\n", " ");
+
+    for (line_index, line) in joined.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap();
+
+        match keyword {
+            ".model" => {}
+            ".inputs" => inputs.extend(words.map(String::from)),
+            ".outputs" => outputs.extend(words.map(String::from)),
+            ".names" => {
+                let mut signals: Vec<String> = words.map(String::from).collect();
+                let output = signals
+                    .pop()
+                    .ok_or_else(|| format!("Line {}: '.names' without signals!", line_index + 1))?;
+                gates.insert(
+                    output.clone(),
+                    Names {
+                        inputs: signals,
+                        rows: vec![],
+                    },
+                );
+                current = Some(output);
+            }
+            ".end" => break,
+            other if other.starts_with('.') => {
+                return Err(format!(
+                    "Line {}: '{}' is not supported, only combinational models are!",
+                    line_index + 1,
+                    other
+                ))
+            }
+            _ => {
+                let gate = current
+                    .as_ref()
+                    .and_then(|name| gates.get_mut(name))
+                    .ok_or_else(|| {
+                        format!("Line {}: cover row outside '.names'!", line_index + 1)
+                    })?;
+                let row: Vec<&str> = line.split_whitespace().collect();
+                let (plane, value) = match row.as_slice() {
+                    [value] if gate.inputs.is_empty() => ("", *value),
+                    [plane, value] => (*plane, *value),
+                    _ => return Err(format!("Line {}: malformed cover row!", line_index + 1)),
+                };
+                if plane.len() != gate.inputs.len() || !matches!(value, "0" | "1") {
+                    return Err(format!("Line {}: malformed cover row!", line_index + 1));
+                }
+                gate.rows
+                    .push((plane.to_string(), value.chars().next().unwrap()));
+            }
+        }
+    }
+
+    let mut resolver = Resolver {
+        inputs: &inputs,
+        gates: &gates,
+        resolved: HashMap::new(),
+        resolving: HashSet::new(),
+    };
+    outputs
+        .into_iter()
+        .map(|output| {
+            let exp = resolver.resolve(&output)?;
+            Ok((output, exp))
+        })
+        .collect()
+}
+
+struct Resolver<'a> {
+    inputs: &'a HashSet<String>,
+    gates: &'a HashMap<String, Names>,
+    resolved: HashMap<String, Expression>,
+    resolving: HashSet<String>,
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, signal: &str) -> Result<Expression, String> {
+        if let Some(exp) = self.resolved.get(signal) {
+            return Ok(exp.clone());
+        }
+        if self.inputs.contains(signal) {
+            let name = VarName::new_from_identifier(signal);
+            return Ok(Expression::Var(Var::new_from_name(name, false)));
+        }
+
+        let gate = self
+            .gates
+            .get(signal)
+            .ok_or_else(|| format!("Signal '{}' is never driven!", signal))?;
+        if !self.resolving.insert(signal.to_string()) {
+            return Err(format!("Signal '{}' depends on itself!", signal));
+        }
+
+        let mut operands = vec![];
+        for input in gate.inputs.iter() {
+            operands.push(self.resolve(input)?);
+        }
+
+        let off_set = gate.rows.first().is_some_and(|(_, value)| *value == '0');
+        if gate
+            .rows
+            .iter()
+            .any(|(_, value)| (*value == '0') != off_set)
+        {
+            return Err(format!("Cover of '{}' mixes ON and OFF rows!", signal));
+        }
+
+        let products: Vec<Expression> = gate
+            .rows
+            .iter()
+            .map(|(plane, _)| {
+                let literals: Vec<Expression> = plane
+                    .chars()
+                    .zip(operands.iter())
+                    .filter_map(|(c, exp)| match c {
+                        '1' => Some(exp.clone()),
+                        '0' => Some(negate(exp.clone())),
+                        _ => None,
+                    })
+                    .collect();
+                product(literals)
+            })
+            .collect();
+
+        let sum = match products.len() {
+            0 => Expression::Constant(Constant::ZERO),
+            1 => products.into_iter().next().unwrap(),
+            _ => Expression::Operation(Operation::OR(Or(products))),
+        };
+        let exp = if off_set { negate(sum) } else { sum };
+
+        self.resolving.remove(signal);
+        self.resolved.insert(signal.to_string(), exp.clone());
+        Ok(exp)
+    }
+}
+
+fn negate(exp: Expression) -> Expression {
+    match exp {
+        Expression::Var(var) => Expression::Var(var.negate()),
+        Expression::Constant(constant) => Expression::Constant(constant.negate()),
+        other => Expression::Operation(Operation::NOT(Not(Box::new(other)))),
+    }
+}
+
+fn product(literals: Vec<Expression>) -> Expression {
+    if literals.len() == 1 {
+        return literals.into_iter().next().unwrap();
+    }
+    if literals.is_empty() {
+        return Expression::Constant(Constant::ONE);
+    }
+
+    let vars: Vec<Var> = literals
+        .iter()
+        .filter_map(|exp| match exp {
+            Expression::Var(var) => Some(*var),
+            _ => None,
+        })
+        .collect();
+    if vars.len() == literals.len() {
+        Expression::Term(Term::new_from_vars(vars))
+    } else {
+        Expression::Operation(Operation::AND(And(literals)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::assert_equivalent;
+
+    #[test]
+    fn round_trip() {
+        let a = Var::new('a', None, false);
+        let x1 = Var::new('x', Some(1), false);
+        let x2 = Var::new('x', Some(2), true);
+        let exp = Expression::Operation(Operation::OR(Or(vec![
+            Expression::Term(Term::new_from_vars(vec![x1, x2])),
+            Expression::Operation(Operation::NOT(Not(Box::new(Expression::Operation(
+                Operation::XOR(Xor(vec![
+                    Expression::Var(a),
+                    Expression::Var(x1),
+                    Expression::Var(x2),
+                ])),
+            ))))),
+        ])));
+
+        let blif = to_blif(&exp, "test", "f");
+        assert!(blif.starts_with(".model test\n.inputs a x1 x2\n.outputs f\n"));
+
+        let outputs = read_blif(&blif).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].0, "f");
+        assert_equivalent(&exp, &outputs[0].1);
+    }
+
+    #[test]
+    fn shares_identical_gates() {
+        let not_a = Expression::Operation(Operation::NOT(Not(Box::new(Expression::Operation(
+            Operation::AND(And(vec![
+                Expression::Var(Var::new('a', None, false)),
+                Expression::Var(Var::new('b', None, false)),
+            ])),
+        )))));
+        let exp = Expression::Operation(Operation::XOR(Xor(vec![not_a.clone(), not_a])));
+
+        assert_eq!(
+            to_blif(&exp, "m", "f"),
+            ".model m\n.inputs a b\n.outputs f\n.names a b n0\n11 1\n.names n0 n1\n0 1\n\
+             .names n1 n1 f\n01 1\n10 1\n.end\n"
+        );
+    }
+
+    #[test]
+    fn fresh_signal_names() {
+        let n0 = VarName::new('n', Some(0));
+        let f = VarName::new('f', None);
+        let exp = Expression::Operation(Operation::NOT(Not(Box::new(Expression::Operation(
+            Operation::AND(And(vec![
+                Expression::Var(Var::new_from_name(n0, false)),
+                Expression::Var(Var::new_from_name(f, true)),
+            ])),
+        )))));
+
+        // neither the gate nor the output may drive an input
+        let blif = to_blif(&exp, "m", "f");
+        assert_eq!(
+            blif,
+            ".model m\n.inputs f n0\n.outputs f_2\n.names f n1\n0 1\n.names n0 n1 n2\n11 1\n\
+             .names n2 f_2\n0 1\n.end\n"
+        );
+
+        let outputs = read_blif(&blif).unwrap();
+        assert_eq!(outputs[0].0, "f_2");
+        assert_equivalent(&exp, &outputs[0].1);
+    }
+
+    #[test]
+    fn reads_off_set_covers_and_constants() {
+        let source = "# comment\n.model m\n.inputs a \\\n b\n.outputs f one\n\
+                      .names a b f\n11 0\n.names one\n1\n.end\n";
+        let outputs = read_blif(source).unwrap();
+
+        let nand = Expression::Operation(Operation::NOT(Not(Box::new(Expression::Term(
+            Term::new_from_vars(vec![Var::new('a', None, false), Var::new('b', None, false)]),
+        )))));
+        assert_equivalent(&outputs[0].1, &nand);
+        assert!(matches!(outputs[1].1, Expression::Constant(Constant::ONE)));
+    }
+
+    #[test]
+    fn reads_tool_generated_names() {
+        let source =
+            ".model m\n.inputs a[0] $abc$n3\n.outputs f\n.names a[0] $abc$n3 f\n11 1\n.end\n";
+        let outputs = read_blif(source).unwrap();
+        let a0 = VarName::new_from_identifier("a[0]");
+        let abc = VarName::new_from_identifier("$abc$n3");
+
+        let and = Expression::Term(Term::new_from_vars(vec![
+            Var::new_from_name(a0, false),
+            Var::new_from_name(abc, false),
+        ]));
+        assert_equivalent(&outputs[0].1, &and);
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let source = ".inputs a\n.outputs f\n.names a g f\n11 1\n.names f g\n1 1\n.end\n";
+        assert!(read_blif(source).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    algorithm::rewrite::{Position, Step},
+    expression::Expression,
+    format::notation::{display_width, Notation},
+};
+
+/// Numbered steps from `start` to the last rewrite with the law used for each, as aligned
+/// text or as a LaTeX `align*` block
+pub fn derivation(start: &Expression, steps: &[Step], notation: Notation) -> String {
+    match notation {
+        Notation::Latex => latex_derivation(start, steps),
+        Notation::Unicode | Notation::Ascii => text_derivation(start, steps, notation),
+    }
+}
+
+/// Operand indices counted from 1, like "2.1" for the first operand of the second operand
+pub fn position_string(position: &Position) -> String {
+    if position.is_empty() {
+        return String::from("root");
+    }
+    position
+        .iter()
+        .map(|i| (i + 1).to_string())
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+fn text_derivation(start: &Expression, steps: &[Step], notation: Notation) -> String {
+    let number_width = steps.len().to_string().len();
+    let lines: Vec<String> = steps
+        .iter()
+        .map(|step| format!("= {}", notation.expression(&step.after)))
+        .collect();
+    let start = notation.expression(start);
+    let width = lines
+        .iter()
+        .map(|line| display_width(line))
+        .chain(std::iter::once(display_width(&start)))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = format!("{}{}\n", " ".repeat(number_width + 2), start);
+    for (i, (line, step)) in lines.iter().zip(steps.iter()).enumerate() {
+        out += &format!(
+            "{:>number_width$}. {}{}   {} at {}\n",
+            i + 1,
+            line,
+            " ".repeat(width - display_width(line)),
+            step.law,
+            position_string(&step.position),
+        );
+    }
+    out
+}
+
+fn latex_derivation(start: &Expression, steps: &[Step]) -> String {
+    let mut lines = vec![format!("  & {}", Notation::Latex.expression(start))];
+    for step in steps.iter() {
+        lines.push(format!(
+            "  &= {} && \\text{{{}}}",
+            Notation::Latex.expression(&step.after),
+            step.law
+        ));
+    }
+    format!(
+        "\\begin{{align*}}\n{}\n\\end{{align*}}\n",
+        lines.join(" \\\\\n")
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algorithm::rewrite::{size, Rewriter, Strategy},
+        parser::grammar::parse,
+    };
+
+    fn steps(source: &str) -> (Expression, Vec<Step>) {
+        let exp = parse(source).unwrap();
+        let steps = Rewriter::standard()
+            .rewrite_steps(&exp, Strategy::Fixpoint(size))
+            .unwrap();
+        (exp, steps)
+    }
+
+    #[test]
+    fn numbered_list() {
+        let (exp, steps) = steps("(a | b) & (a | ~b) & ~~(c | d)");
+        assert_eq!(
+            derivation(&exp, &steps, Notation::Ascii),
+            "   (a | b) & (a | ~b) & ~~(c | d)\n\
+             1. = a & ~~(c | d)                  Combining at root\n\
+             2. = a & (c | d)                    Double negation at 2\n"
+        );
+    }
+
+    #[test]
+    fn latex_align() {
+        let (exp, steps) = steps("a | a b");
+        assert_eq!(
+            derivation(&exp, &steps, Notation::Latex),
+            "\\begin{align*}\n  & a \\vee a \\wedge b \\\\\n  &= a && \\text{Absorption}\n\\end{align*}\n"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    format::notation::current_notation,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+};
+
+#[derive(Clone, Debug)]
+pub struct DotOptions {
+    pub graph_name: String,
+    /// Identical subtrees are drawn once, with an edge from every parent
+    pub share_subtrees: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        DotOptions {
+            graph_name: String::from("expression"),
+            share_subtrees: false,
+        }
+    }
+}
+
+/// Render the expression AST as a Graphviz digraph
+pub fn to_dot(exp: &Expression, options: &DotOptions) -> String {
+    let mut graph = Graph {
+        share_subtrees: options.share_subtrees,
+        nodes: vec![],
+        edges: vec![],
+        known: HashMap::new(),
+    };
+    graph.add(exp);
+
+    let mut out = format!("digraph {} {{\n", escape(&options.graph_name));
+    for (id, (label, shape)) in graph.nodes.iter().enumerate() {
+        out += &format!("    n{} [label={}, shape={}];\n", id, escape(label), shape);
+    }
+    for (parent, child) in graph.edges.iter() {
+        out += &format!("    n{} -> n{};\n", parent, child);
+    }
+    out += "}\n";
+    out
+}
+
+struct Graph {
+    share_subtrees: bool,
+    nodes: Vec<(String, &'static str)>,
+    edges: Vec<(usize, usize)>,
+    /// (label, children) -> node, a subtree is identified by its root and its children
+    known: HashMap<(String, Vec<usize>), usize>,
+}
+
+impl Graph {
+    fn add(&mut self, exp: &Expression) -> usize {
+        let (label, shape, children) = match exp {
+            Expression::Constant(Constant::ZERO) => (String::from("0"), "plaintext", vec![]),
+            Expression::Constant(Constant::ONE) => (String::from("1"), "plaintext", vec![]),
+            Expression::Var(var) => (var.to_string(), "ellipse", vec![]),
+            Expression::Term(term) => (current_notation().product(term), "box", vec![]),
+            Expression::Operation(operation) => {
+                let (label, operands) = match operation {
+                    Operation::NOT(Not(inner)) => ("¬", std::slice::from_ref(inner.as_ref())),
+                    Operation::AND(And(vecexp)) => ("∧", vecexp.as_slice()),
+                    Operation::OR(Or(vecexp)) => ("∨", vecexp.as_slice()),
+                    Operation::XOR(Xor(vecexp)) => ("⊕", vecexp.as_slice()),
+                };
+                let children = operands.iter().map(|e| self.add(e)).collect();
+                (label.to_string(), "circle", children)
+            }
+        };
+
+        let key = (label, children);
+        if self.share_subtrees {
+            if let Some(id) = self.known.get(&key) {
+                return *id;
+            }
+        }
+
+        let id = self.nodes.len();
+        for child in key.1.iter() {
+            self.edges.push((id, *child));
+        }
+        self.nodes.push((key.0.clone(), shape));
+        if self.share_subtrees {
+            self.known.insert(key, id);
+        }
+        id
+    }
+}
+
+fn escape(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::grammar::parse;
+
+    #[test]
+    fn tree() {
+        let exp = parse("~(a b) | 0").unwrap();
+        assert_eq!(
+            to_dot(&exp, &DotOptions::default()),
+            "digraph \"expression\" {\n    n0 [label=\"ab\", shape=box];\n    \
+             n1 [label=\"¬\", shape=circle];\n    n2 [label=\"0\", shape=plaintext];\n    \
+             n3 [label=\"∨\", shape=circle];\n    n1 -> n0;\n    n3 -> n1;\n    n3 -> n2;\n}\n"
+        );
+    }
+
+    #[test]
+    fn long_names_are_separated() {
+        let exp = parse("rst_n ~x300").unwrap();
+        assert!(to_dot(&exp, &DotOptions::default()).contains("label=\"rst_n·x̄₃₀₀\""));
+    }
+
+    #[test]
+    fn shared_subtrees() {
+        let exp = parse("(a | b) & ~(a | b) & a").unwrap();
+
+        let tree = to_dot(&exp, &DotOptions::default());
+        assert_eq!(tree.matches("label=\"a\"").count(), 3);
+
+        let options = DotOptions {
+            share_subtrees: true,
+            ..Default::default()
+        };
+        let shared = to_dot(&exp, &options);
+        assert_eq!(shared.matches("label=\"a\"").count(), 1);
+        assert_eq!(shared.matches("label=\"∨\"").count(), 1);
+        assert_eq!(shared.matches(" -> ").count(), 6);
+    }
+}
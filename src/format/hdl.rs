@@ -0,0 +1,504 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    traits::VarVisibility,
+    var::VarName,
+};
+
+/// How a `VarName` turns into a port name
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortStyle {
+    /// x₁ -> x1
+    Plain,
+    /// x₁ -> x_1
+    Underscore,
+}
+
+/// Where the ports end up, which decides the words they can't be and whether case matters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+    /// BLIF and AIGER, any name goes
+    #[default]
+    Netlist,
+    Verilog,
+    /// case-insensitive, `A` and `a` are the same port
+    Vhdl,
+}
+
+const VERILOG_KEYWORDS: &str = "\
+    always and assign automatic begin buf bufif0 bufif1 case casex casez cell cmos config \
+    deassign default defparam design disable edge else end endcase endconfig endfunction \
+    endgenerate endmodule endprimitive endspecify endtable endtask event for force forever \
+    fork function generate genvar highz0 highz1 if ifnone incdir include initial inout input \
+    instance integer join large liblist library localparam macromodule medium module nand \
+    negedge nmos nor noshowcancelled not notif0 notif1 or output parameter pmos posedge \
+    primitive pull0 pull1 pulldown pullup pulsestyle_ondetect pulsestyle_onevent rcmos real \
+    realtime reg release repeat rnmos rpmos rtran rtranif0 rtranif1 scalared showcancelled \
+    signed small specify specparam strong0 strong1 supply0 supply1 table task time tran \
+    tranif0 tranif1 tri tri0 tri1 triand trior trireg unsigned use uwire vectored wait wand \
+    weak0 weak1 while wire wor xnor xor";
+
+/// VHDL-2008 reserved words and the names `to_vhdl` uses itself, all lowercase
+const VHDL_KEYWORDS: &str = "\
+    abs access after alias all and architecture array assert assume assume_guarantee attribute \
+    begin block body buffer bus case component configuration constant context cover default \
+    disconnect downto else elsif end entity exit fairness file for force function generate \
+    generic group guarded if impure in inertial inout is label library linkage literal loop \
+    map mod nand new next nor not null of on open or others out package parameter port \
+    postponed procedure process property protected pure range record register reject release \
+    rem report restrict restrict_guarantee return rol ror select sequence severity shared \
+    signal sla sll sra srl strong subtype then to transport type unaffected units until use \
+    variable vmode vprop vunit wait when while with xnor xor ieee std_logic std_logic_1164 rtl";
+
+impl Language {
+    /// The form two names are compared in
+    fn key(self, name: &str) -> String {
+        match self {
+            Language::Vhdl => name.to_ascii_lowercase(),
+            _ => name.to_string(),
+        }
+    }
+
+    fn is_reserved(self, name: &str) -> bool {
+        match self {
+            Language::Netlist => false,
+            Language::Verilog => VERILOG_KEYWORDS.split_whitespace().any(|word| word == name),
+            Language::Vhdl => VHDL_KEYWORDS
+                .split_whitespace()
+                .any(|word| word.eq_ignore_ascii_case(name)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HdlOptions {
+    pub module_name: String,
+    pub output_name: String,
+    pub port_style: PortStyle,
+}
+
+impl Default for HdlOptions {
+    fn default() -> Self {
+        HdlOptions {
+            module_name: String::from("circuit"),
+            output_name: String::from("f"),
+            port_style: PortStyle::Plain,
+        }
+    }
+}
+
+struct Syntax {
+    not: &'static str,
+    and: &'static str,
+    or: &'static str,
+    xor: &'static str,
+    zero: &'static str,
+    one: &'static str,
+}
+
+const VERILOG: Syntax = Syntax {
+    not: "~",
+    and: " & ",
+    or: " | ",
+    xor: " ^ ",
+    zero: "1'b0",
+    one: "1'b1",
+};
+
+const VHDL: Syntax = Syntax {
+    not: "not ",
+    and: " and ",
+    or: " or ",
+    xor: " xor ",
+    zero: "'0'",
+    one: "'1'",
+};
+
+/// A port name every format here accepts: ASCII letters, digits and single underscores,
+/// starting with a letter and not ending in an underscore. Other ASCII characters become `_`,
+/// non-ASCII ones their code point, `ä` -> `u00e4`, and a leading digit gets a `p` before it.
+/// Different names can end up the same, `PortNames` tells them apart.
+pub fn port_name(name: &VarName, style: PortStyle) -> String {
+    match (name.subscript(), style) {
+        (Some(subscript), PortStyle::Underscore) => {
+            legal_name(&format!("{}_{}", name.name(), subscript))
+        }
+        _ => legal_name(name.identifier()),
+    }
+}
+
+/// Any text as a port name, the same way `port_name` does it
+fn legal_name(raw: &str) -> String {
+    let mut port = String::new();
+    for c in raw.chars() {
+        let part = match c {
+            _ if c.is_ascii_alphanumeric() => c.to_string(),
+            _ if c.is_ascii() => String::from("_"),
+            _ => format!("_u{:04x}_", c as u32),
+        };
+        for c in part.chars() {
+            if c != '_' || !(port.is_empty() || port.ends_with('_')) {
+                port.push(c);
+            }
+        }
+    }
+    let mut port = port.trim_end_matches('_').to_string();
+    if !port.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        port.insert(0, 'p');
+    }
+    port
+}
+
+/// `base`, or `base_2`, `base_3`, ... if that is taken already
+pub fn fresh_name(base: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    if !is_taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|i| format!("{}_{}", base, i))
+        .find(|name| !is_taken(name))
+        .unwrap()
+}
+
+/// Distinct port names for the inputs of one module, in the order they were given. A port
+/// that is already taken, like `"a b"` after `a_b`, or a reserved word of the language gets a
+/// suffix. Outputs are claimed after the inputs, so an output named like an input is the one
+/// that changes.
+#[derive(Clone, Debug, Default)]
+pub struct PortNames {
+    inputs: Vec<String>,
+    indices: HashMap<VarName, usize>,
+    language: Language,
+    /// in the form `Language::key` compares them
+    taken: HashSet<String>,
+}
+
+impl PortNames {
+    pub fn new(names: &[VarName], style: PortStyle, language: Language) -> Self {
+        let mut ports = PortNames {
+            language,
+            ..Default::default()
+        };
+        ports.add_inputs(names, style);
+        ports
+    }
+
+    /// Like `new`, but the module or entity name is claimed first so no port takes it. Returns
+    /// it legalized like a port.
+    pub fn new_in_module(
+        module_name: &str,
+        names: &[VarName],
+        style: PortStyle,
+        language: Language,
+    ) -> (String, Self) {
+        let mut ports = PortNames {
+            language,
+            ..Default::default()
+        };
+        let module_name = ports.claim(module_name);
+        ports.add_inputs(names, style);
+        (module_name, ports)
+    }
+
+    fn add_inputs(&mut self, names: &[VarName], style: PortStyle) {
+        for name in names.iter() {
+            let port = self.claim_legal(port_name(name, style));
+            self.indices.insert(*name, self.inputs.len());
+            self.inputs.push(port);
+        }
+    }
+
+    /// Another port, like the output, distinct from every port so far
+    pub fn claim(&mut self, name: &str) -> String {
+        self.claim_legal(legal_name(name))
+    }
+
+    fn claim_legal(&mut self, port: String) -> String {
+        let language = self.language;
+        let port = fresh_name(&port, |port| {
+            language.is_reserved(port) || self.taken.contains(&language.key(port))
+        });
+        self.taken.insert(language.key(&port));
+        port
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+    /// Whether `name` is one of the ports
+    pub fn contains(&self, name: &str) -> bool {
+        self.taken.contains(&self.language.key(name))
+    }
+    pub fn get(&self, name: &VarName) -> &str {
+        &self.inputs[self.indices[name]]
+    }
+}
+
+/// A synthesizable Verilog module with a single continuous assignment
+pub fn to_verilog(exp: &Expression, options: &HdlOptions) -> String {
+    let (module_name, mut names) = PortNames::new_in_module(
+        &options.module_name,
+        &exp.get_sorted_variables(),
+        options.port_style,
+        Language::Verilog,
+    );
+    let output = names.claim(&options.output_name);
+    let mut ports: Vec<String> = names
+        .inputs()
+        .iter()
+        .map(|port| format!("    input wire {}", port))
+        .collect();
+    ports.push(format!("    output wire {}", output));
+
+    format!(
+        "module {} (\n{}\n);\n    assign {} = {};\nendmodule\n",
+        module_name,
+        ports.join(",\n"),
+        output,
+        emit(exp, &VERILOG, &names)
+    )
+}
+
+/// A VHDL entity with a dataflow architecture
+pub fn to_vhdl(exp: &Expression, options: &HdlOptions) -> String {
+    let (module_name, mut names) = PortNames::new_in_module(
+        &options.module_name,
+        &exp.get_sorted_variables(),
+        options.port_style,
+        Language::Vhdl,
+    );
+    let output = names.claim(&options.output_name);
+    let mut ports: Vec<String> = names
+        .inputs()
+        .iter()
+        .map(|port| format!("        {} : in std_logic", port))
+        .collect();
+    ports.push(format!("        {} : out std_logic", output));
+
+    format!(
+        "library ieee;\nuse ieee.std_logic_1164.all;\n\n\
+         entity {name} is\n    port (\n{}\n    );\nend entity {name};\n\n\
+         architecture rtl of {name} is\nbegin\n    {} <= {};\nend architecture rtl;\n",
+        ports.join(";\n"),
+        output,
+        emit(exp, &VHDL, &names),
+        name = module_name
+    )
+}
+
+fn emit(exp: &Expression, syntax: &Syntax, ports: &PortNames) -> String {
+    match exp {
+        Expression::Constant(Constant::ZERO) => syntax.zero.to_string(),
+        Expression::Constant(Constant::ONE) => syntax.one.to_string(),
+        Expression::Var(var) => {
+            let name = ports.get(&var.name()).to_string();
+            if var.is_negated() {
+                format!("{}{}", syntax.not, name)
+            } else {
+                name
+            }
+        }
+        Expression::Term(term) => {
+            let literals: Vec<String> = term
+                .vars()
+                .iter()
+                .map(|var| emit(&Expression::Var(*var), syntax, ports))
+                .collect();
+            if literals.is_empty() {
+                syntax.one.to_string()
+            } else {
+                literals.join(syntax.and)
+            }
+        }
+        Expression::Operation(Operation::NOT(Not(inner))) => {
+            format!("{}{}", syntax.not, emit_operand(inner, syntax, ports, true))
+        }
+        Expression::Operation(Operation::AND(And(vecexp))) => {
+            emit_list(vecexp, syntax.and, syntax.one, syntax, ports)
+        }
+        Expression::Operation(Operation::OR(Or(vecexp))) => {
+            emit_list(vecexp, syntax.or, syntax.zero, syntax, ports)
+        }
+        Expression::Operation(Operation::XOR(Xor(vecexp))) => {
+            emit_list(vecexp, syntax.xor, syntax.zero, syntax, ports)
+        }
+    }
+}
+
+fn emit_list(
+    vecexp: &[Expression],
+    separator: &str,
+    empty: &str,
+    syntax: &Syntax,
+    ports: &PortNames,
+) -> String {
+    if vecexp.is_empty() {
+        return empty.to_string();
+    }
+    vecexp
+        .iter()
+        .map(|exp| emit_operand(exp, syntax, ports, false))
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+/// VHDL has no precedence between `and`, `or` and `xor`, so any operand built from a binary
+/// operator gets parenthesized. `for_not` also wraps a negated literal, `~~a` isn't valid VHDL.
+fn emit_operand(exp: &Expression, syntax: &Syntax, ports: &PortNames, for_not: bool) -> String {
+    let needs_parens = match exp {
+        Expression::Constant(_) => false,
+        Expression::Var(var) => for_not && var.is_negated(),
+        Expression::Term(term) => term.vars().len() > 1 || (for_not && !term.vars().is_empty()),
+        Expression::Operation(Operation::NOT(Not(_))) => for_not,
+        Expression::Operation(
+            Operation::AND(And(vecexp)) | Operation::OR(Or(vecexp)) | Operation::XOR(Xor(vecexp)),
+        ) => vecexp.len() > 1 || for_not,
+    };
+
+    let out = emit(exp, syntax, ports);
+    if needs_parens {
+        format!("({})", out)
+    } else {
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{term::Term, var::Var};
+
+    fn example() -> Expression {
+        // ~x1 | ~x2 ~x3 x4
+        Expression::Operation(Operation::OR(Or(vec![
+            Expression::Var(Var::new('x', Some(1), true)),
+            Expression::Term(Term::new_from_vars(vec![
+                Var::new('x', Some(2), true),
+                Var::new('x', Some(3), true),
+                Var::new('x', Some(4), false),
+            ])),
+        ])))
+    }
+
+    #[test]
+    fn verilog_module() {
+        let options = HdlOptions {
+            module_name: String::from("example"),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_verilog(&example(), &options),
+            "module example (\n    input wire x1,\n    input wire x2,\n    input wire x3,\n    \
+            input wire x4,\n    output wire f\n);\n    assign f = ~x1 | (~x2 & ~x3 & x4);\n\
+             endmodule\n"
+        );
+    }
+
+    #[test]
+    fn vhdl_entity() {
+        let options = HdlOptions {
+            port_style: PortStyle::Underscore,
+            ..Default::default()
+        };
+        let vhdl = to_vhdl(&example(), &options);
+
+        assert!(vhdl.contains("entity circuit is\n"));
+        assert!(vhdl.contains("        x_1 : in std_logic;\n"));
+        assert!(vhdl.contains("        f : out std_logic\n"));
+        assert!(vhdl.contains("    f <= not x_1 or (not x_2 and not x_3 and x_4);\n"));
+    }
+
+    #[test]
+    fn nested_negations_are_wrapped() {
+        let exp = Expression::Operation(Operation::NOT(Not(Box::new(Expression::Operation(
+            Operation::XOR(Xor(vec![
+                Expression::Var(Var::new('a', None, false)),
+                Expression::Constant(Constant::ONE),
+            ])),
+        )))));
+        let double = Expression::Operation(Operation::NOT(Not(Box::new(Expression::Var(
+            Var::new('a', None, true),
+        )))));
+
+        let ports = PortNames::new(&[VarName::new('a', None)], PortStyle::Plain, Language::Vhdl);
+        assert_eq!(emit(&exp, &VERILOG, &ports), "~(a ^ 1'b1)");
+        assert_eq!(emit(&double, &VHDL, &ports), "not (not a)");
+    }
+
+    #[test]
+    fn legal_port_names() {
+        let port = |name: &str| port_name(&VarName::new_from_identifier(name), PortStyle::Plain);
+        assert_eq!(port("rst_n"), "rst_n");
+        assert_eq!(port("reset n"), "reset_n");
+        assert_eq!(port("1x"), "p1x");
+        assert_eq!(port("_a__b_"), "a_b");
+        assert_eq!(port("zähler"), "z_u00e4_hler");
+        assert_eq!(port("ß"), "u00df");
+        assert_eq!(
+            port_name(&VarName::new('x', Some(1)), PortStyle::Underscore),
+            "x_1"
+        );
+    }
+
+    #[test]
+    fn distinct_port_names() {
+        let names = ["a_b", "a b", "a-b"].map(VarName::new_from_identifier);
+        let ports = PortNames::new(&names, PortStyle::Plain, Language::Netlist);
+        assert_eq!(ports.inputs(), ["a_b", "a_b_2", "a_b_3"]);
+        assert_eq!(ports.get(&names[1]), "a_b_2");
+    }
+
+    #[test]
+    fn reserved_words_and_output_collisions() {
+        let exp = Expression::Operation(Operation::AND(And(["module", "wire", "f"]
+            .map(|name| Expression::Var(Var::new_from_name(name.parse().unwrap(), false)))
+            .to_vec())));
+        let verilog = to_verilog(&exp, &HdlOptions::default());
+        assert!(verilog.contains("    input wire f,\n    input wire module_2,\n"));
+        assert!(verilog.contains("    input wire wire_2,\n    output wire f_2\n"));
+        assert!(verilog.contains("    assign f_2 = module_2 & wire_2 & f;\n"));
+    }
+
+    #[test]
+    fn legal_module_names() {
+        let exp = Expression::Term(Term::new_from_vars(vec![
+            Var::new('a', None, false),
+            Var::new_from_name(VarName::new_from_identifier("my mod"), false),
+        ]));
+        let options = HdlOptions {
+            module_name: String::from("my mod"),
+            output_name: String::from("my_mod"),
+            ..Default::default()
+        };
+        let verilog = to_verilog(&exp, &options);
+        assert!(verilog.starts_with("module my_mod (\n    input wire a,\n"));
+        assert!(verilog.contains("    input wire my_mod_2,\n    output wire my_mod_3\n"));
+
+        let options = HdlOptions {
+            module_name: String::from("entity"),
+            ..Default::default()
+        };
+        let vhdl = to_vhdl(&exp, &options);
+        assert!(vhdl.contains("entity entity_2 is\n"));
+        assert!(vhdl.contains("architecture rtl of entity_2 is\n"));
+    }
+
+    #[test]
+    fn vhdl_ignores_case() {
+        let exp = Expression::Operation(Operation::XOR(Xor(["A", "a", "Signal"]
+            .map(|name| Expression::Var(Var::new_from_name(name.parse().unwrap(), false)))
+            .to_vec())));
+        let options = HdlOptions {
+            output_name: String::from("IN"),
+            ..Default::default()
+        };
+        let vhdl = to_vhdl(&exp, &options);
+        assert!(vhdl.contains("        A : in std_logic;\n        Signal_2 : in std_logic;\n"));
+        assert!(vhdl.contains("        a_2 : in std_logic;\n        IN_2 : out std_logic\n"));
+        assert!(vhdl.contains("    IN_2 <= A xor a_2 xor Signal_2;\n"));
+    }
+}
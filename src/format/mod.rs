@@ -0,0 +1,10 @@
+pub mod aiger;
+pub mod blif;
+pub mod derivation;
+pub mod dot;
+pub mod hdl;
+pub mod notation;
+pub mod pla;
+pub mod quine;
+pub mod table;
+pub mod tree;
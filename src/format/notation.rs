@@ -0,0 +1,325 @@
+use std::cell::Cell;
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    term::Term,
+    var::Var,
+};
+
+/// How `Display` writes variables, terms and expressions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Notation {
+    /// x̄₁ ∨ x₂
+    #[default]
+    Unicode,
+    /// ~x1 | x2, the syntax the parser accepts. Anything the parser builds prints back into
+    /// the same structure, while shapes it never builds, like an AND of plain variables,
+    /// come back as the equivalent formula the parser prefers.
+    Ascii,
+    /// \lnot x_{1} \vee x_{2}
+    Latex,
+}
+
+thread_local! {
+    static NOTATION: Cell<Notation> = const { Cell::new(Notation::Unicode) };
+}
+
+/// Switch the notation every `Display` on this thread uses from now on
+pub fn set_notation(notation: Notation) {
+    NOTATION.with(|current| current.set(notation));
+}
+
+pub fn current_notation() -> Notation {
+    NOTATION.with(|current| current.get())
+}
+
+/// Columns `text` takes up in a terminal, combining marks like the bar over x̄ take none
+pub fn display_width(text: &str) -> usize {
+    text.chars()
+        .filter(|c| !('\u{0300}'..='\u{036F}').contains(c))
+        .count()
+}
+
+fn latex_escape(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '_' | '&' | '%' | '$' | '#' | '{' | '}' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+const PRECEDENCE_OR: u8 = 0;
+const PRECEDENCE_XOR: u8 = 1;
+const PRECEDENCE_AND: u8 = 2;
+const PRECEDENCE_NOT: u8 = 3;
+const PRECEDENCE_ATOM: u8 = 4;
+
+struct Symbols {
+    not: &'static str,
+    and: &'static str,
+    or: &'static str,
+    xor: &'static str,
+}
+
+impl Notation {
+    fn symbols(self) -> Symbols {
+        match self {
+            Notation::Unicode => Symbols {
+                not: "¬",
+                and: " ∧ ",
+                or: " ∨ ",
+                xor: " ⊕ ",
+            },
+            Notation::Ascii => Symbols {
+                not: "~",
+                and: " & ",
+                or: " | ",
+                xor: " ^ ",
+            },
+            Notation::Latex => Symbols {
+                not: "\\lnot ",
+                and: " \\wedge ",
+                or: " \\vee ",
+                xor: " \\oplus ",
+            },
+        }
+    }
+
+    pub fn var(self, var: &Var) -> String {
+        let name = var.name();
+        match self {
+            Notation::Unicode => name.get_string(var.is_negated()),
+            Notation::Ascii | Notation::Latex => {
+                let identifier = match (self, name.subscript()) {
+                    (Notation::Latex, Some(subscript)) => {
+                        format!("{}_{{{}}}", name.name(), subscript)
+                    }
+                    (Notation::Latex, None) if name.identifier().chars().count() == 1 => {
+                        name.identifier().to_string()
+                    }
+                    (Notation::Latex, None) if name.is_plain() => {
+                        format!("\\mathit{{{}}}", latex_escape(name.identifier()))
+                    }
+                    (Notation::Latex, None) => {
+                        format!("\\text{{{}}}", latex_escape(name.identifier()))
+                    }
+                    _ => name.get_identifier(),
+                };
+                if var.is_negated() {
+                    format!("{}{}", self.symbols().not, identifier)
+                } else {
+                    identifier
+                }
+            }
+        }
+    }
+
+    pub fn term(self, term: &Term) -> String {
+        match self {
+            Notation::Unicode => format!("({})", self.product(term)),
+            _ => self.product(term),
+        }
+    }
+
+    /// The literals of a term without the parentheses Unicode puts around them. Unicode writes
+    /// single letters next to each other, x̄₁x₂, but `·` separates longer names, rst_n·x₃₀₀.
+    pub fn product(self, term: &Term) -> String {
+        let literals: Vec<String> = term.vars().iter().map(|var| self.var(var)).collect();
+        match self {
+            Notation::Unicode => {
+                let is_long = |var: &Var| var.name().name().chars().count() > 1;
+                if term.vars().iter().any(is_long) {
+                    literals.join("·")
+                } else {
+                    literals.concat()
+                }
+            }
+            // concatenation, like the parser accepts
+            Notation::Ascii if !literals.is_empty() => literals.join(" "),
+            Notation::Latex if !literals.is_empty() => literals.join(self.symbols().and),
+            _ => String::from("1"),
+        }
+    }
+
+    pub fn constant(self, constant: Constant) -> String {
+        match constant {
+            Constant::ZERO => String::from("0"),
+            Constant::ONE => String::from("1"),
+        }
+    }
+
+    pub fn expression(self, exp: &Expression) -> String {
+        match exp {
+            Expression::Constant(constant) => self.constant(*constant),
+            Expression::Var(var) => self.var(var),
+            Expression::Term(term) => self.term(term),
+            Expression::Operation(operation) => self.operation(operation),
+        }
+    }
+
+    pub fn operation(self, operation: &Operation) -> String {
+        let symbols = self.symbols();
+        match operation {
+            Operation::NOT(Not(inner)) => {
+                format!("{}{}", symbols.not, self.operand(inner, PRECEDENCE_NOT))
+            }
+            Operation::AND(And(vecexp)) => self.list(vecexp, symbols.and, "1", PRECEDENCE_AND),
+            Operation::OR(Or(vecexp)) => self.list(vecexp, symbols.or, "0", PRECEDENCE_OR),
+            Operation::XOR(Xor(vecexp)) => self.list(vecexp, symbols.xor, "0", PRECEDENCE_XOR),
+        }
+    }
+
+    fn list(self, vecexp: &[Expression], separator: &str, empty: &str, precedence: u8) -> String {
+        match vecexp {
+            [] => empty.to_string(),
+            [exp] => self.expression(exp),
+            _ => vecexp
+                .iter()
+                .map(|exp| self.operand(exp, precedence))
+                .collect::<Vec<String>>()
+                .join(separator),
+        }
+    }
+
+    /// How tightly the top of `exp` binds, same as in the parser
+    fn precedence(self, exp: &Expression) -> u8 {
+        match exp {
+            Expression::Constant(_) | Expression::Var(_) => PRECEDENCE_ATOM,
+            Expression::Term(term) => match (self, term.vars().len()) {
+                // Unicode terms carry their own parentheses
+                (Notation::Unicode, _) | (_, 0 | 1) => PRECEDENCE_ATOM,
+                _ => PRECEDENCE_AND,
+            },
+            Expression::Operation(Operation::NOT(Not(_))) => PRECEDENCE_NOT,
+            Expression::Operation(
+                Operation::AND(And(vecexp))
+                | Operation::OR(Or(vecexp))
+                | Operation::XOR(Xor(vecexp)),
+            ) if vecexp.len() < 2 => vecexp
+                .first()
+                .map_or(PRECEDENCE_ATOM, |exp| self.precedence(exp)),
+            Expression::Operation(Operation::AND(And(_))) => PRECEDENCE_AND,
+            Expression::Operation(Operation::XOR(Xor(_))) => PRECEDENCE_XOR,
+            Expression::Operation(Operation::OR(Or(_))) => PRECEDENCE_OR,
+        }
+    }
+
+    /// Operands binding as loosely as their parent get parenthesized. Equal precedence counts
+    /// too, so `(a | b) | c` doesn't come back from the parser as `a | b | c`.
+    fn operand(self, exp: &Expression, parent: u8) -> String {
+        let out = self.expression(exp);
+        let needs_parens = match parent {
+            PRECEDENCE_NOT => self.precedence(exp) < PRECEDENCE_NOT,
+            _ => self.precedence(exp) <= parent,
+        };
+
+        match (needs_parens, self) {
+            (false, _) => out,
+            (true, Notation::Latex) => format!("\\left({}\\right)", out),
+            (true, _) => format!("({})", out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::grammar::parse, testing::Generator};
+
+    #[test]
+    fn backends() {
+        // a & x2 is parsed as the term a x2
+        let exp = parse("~x1 | ~(a & x2) ^ x3 ~y").unwrap();
+
+        assert_eq!(
+            Notation::Unicode.expression(&exp),
+            "x\u{304}₁ ∨ ¬(ax₂) ⊕ (x₃y\u{304})"
+        );
+        assert_eq!(Notation::Ascii.expression(&exp), "~x1 | ~(a x2) ^ x3 ~y");
+        assert_eq!(
+            Notation::Latex.expression(&exp),
+            "\\lnot x_{1} \\vee \\lnot \\left(a \\wedge x_{2}\\right) \\oplus x_{3} \\wedge \\lnot y"
+        );
+    }
+
+    #[test]
+    fn long_names() {
+        let exp = parse("~enable | rst_n x300 ^ \"reset n\"").unwrap();
+        assert_eq!(
+            Notation::Unicode.expression(&exp),
+            "¬enable ∨ (rst_n·x₃₀₀) ⊕ \"reset n\""
+        );
+        assert_eq!(
+            Notation::Ascii.expression(&exp),
+            "~enable | rst_n x300 ^ \"reset n\""
+        );
+        assert_eq!(
+            Notation::Latex.expression(&exp),
+            "\\lnot \\mathit{enable} \\vee \\mathit{rst\\_n} \\wedge x_{300} \\oplus \\text{reset n}"
+        );
+    }
+
+    #[test]
+    fn width_ignores_combining_marks() {
+        assert_eq!(display_width("x\u{304}₁ ∨ a"), 6);
+    }
+
+    #[test]
+    fn display_follows_current_notation() {
+        let exp = parse("~x1 | x2").unwrap();
+        assert_eq!(exp.to_string(), "x̄₁ ∨ x₂");
+
+        set_notation(Notation::Latex);
+        assert_eq!(exp.to_string(), "\\lnot x_{1} \\vee x_{2}");
+        set_notation(Notation::Ascii);
+        assert_eq!(exp.to_string(), "~x1 | x2");
+        set_notation(Notation::Unicode);
+    }
+
+    fn assert_round_trip(exp: &Expression) {
+        let printed = Notation::Ascii.expression(exp);
+        let parsed =
+            parse(&printed).unwrap_or_else(|e| panic!("'{}' doesn't parse: {}", printed, e));
+        assert_eq!(parsed, *exp, "printed as '{}'", printed);
+    }
+
+    #[test]
+    fn round_trip_written() {
+        for source in [
+            "~x1 | ~x2 ~x3 x4",
+            "(a | b) | c",
+            "a | (b | c) | x1",
+            "(a b) & c",
+            "a b & (c | 0)",
+            "~(a b) ^ ~~(a | b)",
+            "(a ^ b) ^ (a ^ b) & x12",
+            "~(a & 1) & (b ^ 0 ^ 1)",
+            "~enable & (rst_n | \"reset n\" x300)",
+            "\"we\\\"ird\" | \"back\\\\slash\" & a",
+            "a ^ b c | ~b & (c | a)",
+        ] {
+            assert_round_trip(&parse(source).unwrap());
+        }
+    }
+
+    #[test]
+    fn round_trip_generated() {
+        let mut generator = Generator(0x5eed);
+        for _ in 0..500 {
+            assert_round_trip(&generator.expression(4));
+        }
+    }
+
+    #[test]
+    fn minimal_parentheses() {
+        let exp = parse("(a & (b | c)) | ((a ^ b) & ~c)").unwrap();
+        assert_eq!(
+            Notation::Ascii.expression(&exp),
+            "a & (b | c) | (a ^ b) & ~c"
+        );
+    }
+}
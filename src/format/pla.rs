@@ -0,0 +1,640 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    algorithm::{consensus, mintermmini},
+    term::Term,
+    truthtable::MAX_VARIABLES,
+    var::{Var, VarName},
+};
+
+// cubes are packed into a u64 when expanding them, and `minterms` walks every row
+const MAX_INPUTS: usize = MAX_VARIABLES;
+
+// covering compares every prime against every ON minterm
+const MAX_COVERED_MINTERMS: u64 = 1 << 16;
+
+/// Which sets the output plane of a `.pla` file describes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaType {
+    /// ON-set only
+    F,
+    /// ON-set and don't care set
+    FD,
+    /// ON-set and OFF-set, everything else is a don't care
+    FR,
+    /// ON-set, don't care set and OFF-set
+    FDR,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputValue {
+    One,
+    Zero,
+    DontCare,
+    /// `~`, the cube means nothing for this output
+    Unused,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cube {
+    /// `None` stands for `-`, the input isn't part of the cube
+    pub inputs: Vec<Option<bool>>,
+    pub outputs: Vec<OutputValue>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pla {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub input_labels: Option<Vec<String>>,
+    pub output_labels: Option<Vec<String>>,
+    pub kind: PlaType,
+    pub cubes: Vec<Cube>,
+}
+
+impl PlaType {
+    fn has_dont_cares(self) -> bool {
+        matches!(self, PlaType::FD | PlaType::FDR)
+    }
+    fn has_off_set(self) -> bool {
+        matches!(self, PlaType::FR | PlaType::FDR)
+    }
+}
+
+impl Pla {
+    pub fn parse(source: &str) -> Result<Pla, String> {
+        let mut inputs: Option<usize> = None;
+        let mut outputs: Option<usize> = None;
+        let mut input_labels: Option<Vec<String>> = None;
+        let mut output_labels: Option<Vec<String>> = None;
+        let mut kind = PlaType::FD;
+        let mut cubes = vec![];
+
+        for (line_index, line) in source.lines().enumerate() {
+            let line_number = line_index + 1;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap();
+
+            if !keyword.starts_with('.') {
+                let (ni, no) = match (inputs, outputs) {
+                    (Some(ni), Some(no)) => (ni, no),
+                    _ => {
+                        return Err(format!(
+                            "Line {}: cube appears before '.i' and '.o'!",
+                            line_number
+                        ))
+                    }
+                };
+                cubes.push(Self::parse_cube(line, ni, no, line_number)?);
+                continue;
+            }
+
+            match keyword {
+                ".i" | ".o" if !cubes.is_empty() => {
+                    return Err(format!(
+                        "Line {}: '{}' after the first cube would change its width!",
+                        line_number, keyword
+                    ))
+                }
+                ".i" => inputs = Some(Self::parse_count(words.next(), line_number)?),
+                ".o" => outputs = Some(Self::parse_count(words.next(), line_number)?),
+                ".ilb" => input_labels = Some(words.map(String::from).collect()),
+                ".ob" => output_labels = Some(words.map(String::from).collect()),
+                ".type" => {
+                    kind = match words.next() {
+                        Some("f") => PlaType::F,
+                        Some("fd") => PlaType::FD,
+                        Some("fr") => PlaType::FR,
+                        Some("fdr") => PlaType::FDR,
+                        other => {
+                            return Err(format!(
+                                "Line {}: unsupported type '{}'!",
+                                line_number,
+                                other.unwrap_or("")
+                            ))
+                        }
+                    }
+                }
+                ".e" | ".end" => break,
+                // .p is only a hint, other keywords don't change the meaning of the cover
+                _ => continue,
+            }
+        }
+
+        let inputs = inputs.ok_or("Missing '.i' declaration!")?;
+        let outputs = outputs.ok_or("Missing '.o' declaration!")?;
+
+        if inputs > MAX_INPUTS {
+            return Err(format!(
+                "At most {} inputs are supported, got {}!",
+                MAX_INPUTS, inputs
+            ));
+        }
+        if let Some(labels) = &input_labels {
+            if labels.len() != inputs {
+                return Err(format!(
+                    "'.ilb' names {} inputs, but '.i' declares {}!",
+                    labels.len(),
+                    inputs
+                ));
+            }
+        }
+        if let Some(labels) = &output_labels {
+            if labels.len() != outputs {
+                return Err(format!(
+                    "'.ob' names {} outputs, but '.o' declares {}!",
+                    labels.len(),
+                    outputs
+                ));
+            }
+        }
+
+        Ok(Pla {
+            inputs,
+            outputs,
+            input_labels,
+            output_labels,
+            kind,
+            cubes,
+        })
+    }
+
+    fn parse_count(word: Option<&str>, line_number: usize) -> Result<usize, String> {
+        word.and_then(|w| w.parse::<usize>().ok())
+            .ok_or_else(|| format!("Line {}: expected a number!", line_number))
+    }
+
+    fn parse_cube(
+        line: &str,
+        inputs: usize,
+        outputs: usize,
+        line_number: usize,
+    ) -> Result<Cube, String> {
+        // the input and output planes may or may not be separated by whitespace
+        let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if chars.len() != inputs + outputs {
+            return Err(format!(
+                "Line {}: cube '{}' should have {} inputs and {} outputs!",
+                line_number, line, inputs, outputs
+            ));
+        }
+
+        let mut cube = Cube {
+            inputs: Vec::with_capacity(inputs),
+            outputs: Vec::with_capacity(outputs),
+        };
+
+        for c in chars[..inputs].iter() {
+            cube.inputs.push(match c {
+                '0' => Some(false),
+                '1' => Some(true),
+                '-' | '2' => None,
+                other => {
+                    return Err(format!(
+                        "Line {}: '{}' is not a valid input value!",
+                        line_number, other
+                    ))
+                }
+            });
+        }
+        for c in chars[inputs..].iter() {
+            cube.outputs.push(match c {
+                '1' | '4' => OutputValue::One,
+                '0' | '3' => OutputValue::Zero,
+                '-' | '2' => OutputValue::DontCare,
+                '~' => OutputValue::Unused,
+                other => {
+                    return Err(format!(
+                        "Line {}: '{}' is not a valid output value!",
+                        line_number, other
+                    ))
+                }
+            });
+        }
+
+        Ok(cube)
+    }
+
+    /// Returns the minterms of the ON-set and the don't care set of the given output. Walks every
+    /// row for `.type fr` and `fdr`, `minimize` works on the cubes instead.
+    pub fn minterms(&self, output: usize) -> Result<(Vec<Term>, Vec<Term>), String> {
+        let mut on: BTreeSet<u64> = BTreeSet::new();
+        let mut dont_care: BTreeSet<u64> = BTreeSet::new();
+        let mut off: BTreeSet<u64> = BTreeSet::new();
+
+        for (cube, value) in self.output_cubes(output)? {
+            let set = match value {
+                OutputValue::One => &mut on,
+                OutputValue::DontCare => &mut dont_care,
+                _ => &mut off,
+            };
+            set.extend(Self::expand_cube(&cube.inputs));
+        }
+
+        if let Some(minterm) = on.intersection(&off).next() {
+            return Err(format!(
+                "Minterm {} is in both the ON-set and the OFF-set of output {}!",
+                minterm, output
+            ));
+        }
+
+        if self.kind.has_off_set() {
+            // everything not explicitly specified is a don't care
+            for minterm in 0..(1u64 << self.inputs) {
+                if !on.contains(&minterm) && !off.contains(&minterm) {
+                    dont_care.insert(minterm);
+                }
+            }
+        }
+
+        dont_care.retain(|minterm| !on.contains(minterm));
+
+        let names = VarName::numbered(self.inputs);
+        let to_terms = |set: &BTreeSet<u64>| -> Vec<Term> {
+            set.iter()
+                .map(|minterm| Term::new_from_minterm_of(&names, *minterm))
+                .collect()
+        };
+
+        Ok((to_terms(&on), to_terms(&dont_care)))
+    }
+
+    /// The primes come from iterated consensus on the cubes, only covering them walks the
+    /// minterms of the ON-set
+    fn minimize_output(&self, output: usize, names: &[VarName]) -> Result<Vec<Term>, String> {
+        let mut on: Vec<Term> = vec![];
+        let mut dont_care: Vec<Term> = vec![];
+        let mut off: Vec<Term> = vec![];
+        let mut on_minterms: BTreeSet<u64> = BTreeSet::new();
+        for (cube, value) in self.output_cubes(output)? {
+            let term = Self::cube_to_term(names, &cube.inputs);
+            match value {
+                OutputValue::One => {
+                    let size = 1u64 << (self.inputs - term.vars().len());
+                    if on_minterms.len() as u64 + size > MAX_COVERED_MINTERMS {
+                        return Err(format!(
+                            "Output {} has more than {} ON minterms, covering them wouldn't finish!",
+                            output, MAX_COVERED_MINTERMS
+                        ));
+                    }
+                    on_minterms.extend(Self::expand_cube(&cube.inputs));
+                    on.push(term)
+                }
+                OutputValue::DontCare => dont_care.push(term),
+                _ => off.push(term),
+            }
+        }
+
+        let overlap = on
+            .iter()
+            .find_map(|a| off.iter().find_map(|b| a.conjoin(b)));
+        if let Some(overlap) = overlap {
+            return Err(format!(
+                "{} is in both the ON-set and the OFF-set of output {}!",
+                overlap, output
+            ));
+        }
+
+        // with an OFF-set the function may be anything outside of it, else up to ON and DC
+        let upper = if self.kind.has_off_set() {
+            off.iter()
+                .fold(vec![Term::new_from_vars(vec![])], |rest, cube| {
+                    Self::remove_cube(rest, cube)
+                })
+        } else {
+            on.iter().chain(dont_care.iter()).cloned().collect()
+        };
+        let primes = consensus::iterated_consensus(upper)?;
+
+        let minterms: Vec<Term> = on_minterms
+            .into_iter()
+            .map(|minterm| Term::new_from_minterm_of(names, minterm))
+            .collect();
+
+        let mut cover = mintermmini::select_cover(&primes, &minterms);
+        cover.sort();
+        Ok(cover)
+    }
+
+    /// The points of `terms` outside of `cube`. Each term overlapping it splits into one term
+    /// per literal of the cube it doesn't fix yet, with that literal negated.
+    fn remove_cube(terms: Vec<Term>, cube: &Term) -> Vec<Term> {
+        let mut rest = vec![];
+        for term in terms {
+            if term.conjoin(cube).is_none() {
+                rest.push(term);
+                continue;
+            }
+            for var in cube.vars().iter().filter(|var| !term.has_var(**var)) {
+                let outside = Term::new_from_vars(vec![var.negate()]);
+                rest.extend(term.conjoin(&outside));
+            }
+        }
+        rest
+    }
+
+    fn cube_to_term(names: &[VarName], inputs: &[Option<bool>]) -> Term {
+        Term::new_from_vars(
+            names
+                .iter()
+                .zip(inputs.iter())
+                .filter_map(|(name, input)| input.map(|bit| Var::new_from_name(*name, !bit)))
+                .collect(),
+        )
+    }
+
+    fn expand_cube(inputs: &[Option<bool>]) -> Vec<u64> {
+        let mut minterms = vec![0u64];
+        for input in inputs.iter() {
+            minterms = match input {
+                Some(bit) => minterms
+                    .into_iter()
+                    .map(|m| (m << 1) | *bit as u64)
+                    .collect(),
+                None => minterms
+                    .into_iter()
+                    .flat_map(|m| [m << 1, (m << 1) | 1])
+                    .collect(),
+            };
+        }
+        minterms
+    }
+
+    /// The cubes that say something about `output` under this PLA's type, with what they say
+    fn output_cubes(&self, output: usize) -> Result<Vec<(&Cube, OutputValue)>, String> {
+        if self.inputs > MAX_INPUTS {
+            return Err(format!(
+                "At most {} inputs are supported, got {}!",
+                MAX_INPUTS, self.inputs
+            ));
+        }
+        if output >= self.outputs {
+            return Err(format!("There is no output #{}!", output));
+        }
+
+        let mut cubes = vec![];
+        for cube in self.cubes.iter() {
+            if cube.inputs.len() != self.inputs || cube.outputs.len() != self.outputs {
+                return Err(format!(
+                    "A cube has {} inputs and {} outputs, the PLA {} and {}!",
+                    cube.inputs.len(),
+                    cube.outputs.len(),
+                    self.inputs,
+                    self.outputs
+                ));
+            }
+            match cube.outputs[output] {
+                OutputValue::One => cubes.push((cube, OutputValue::One)),
+                OutputValue::DontCare if self.kind.has_dont_cares() => {
+                    cubes.push((cube, OutputValue::DontCare))
+                }
+                OutputValue::Zero if self.kind.has_off_set() => {
+                    cubes.push((cube, OutputValue::Zero))
+                }
+                _ => continue,
+            }
+        }
+        Ok(cubes)
+    }
+
+    /// Minimize every output, returning the covers as a new `.type f` PLA
+    pub fn minimize(&self) -> Result<Pla, String> {
+        let names = VarName::numbered(self.inputs);
+        let mut covers = vec![];
+        for output in 0..self.outputs {
+            covers.push(self.minimize_output(output, &names)?);
+        }
+
+        let mut pla = Pla::from_covers(self.inputs, &covers);
+        pla.input_labels = self.input_labels.clone();
+        pla.output_labels = self.output_labels.clone();
+        Ok(pla)
+    }
+
+    /// Build a PLA with one output per cover. Input `i` is the variable `x_(i+1)`, same as in
+    /// `Term::new_from_minterm`
+    pub fn from_covers(inputs: usize, covers: &[Vec<Term>]) -> Pla {
+        let mut cubes: Vec<Cube> = vec![];
+        let names = VarName::numbered(inputs);
+
+        for (output, cover) in covers.iter().enumerate() {
+            for term in cover.iter() {
+                let cube_inputs = Self::term_to_cube(&names, term);
+
+                // outputs sharing a product term share the row
+                match cubes.iter_mut().find(|cube| cube.inputs == cube_inputs) {
+                    Some(cube) => cube.outputs[output] = OutputValue::One,
+                    None => {
+                        let mut outputs = vec![OutputValue::Zero; covers.len()];
+                        outputs[output] = OutputValue::One;
+                        cubes.push(Cube {
+                            inputs: cube_inputs,
+                            outputs,
+                        });
+                    }
+                }
+            }
+        }
+
+        Pla {
+            inputs,
+            outputs: covers.len(),
+            input_labels: None,
+            output_labels: None,
+            kind: PlaType::F,
+            cubes,
+        }
+    }
+
+    fn term_to_cube(names: &[VarName], term: &Term) -> Vec<Option<bool>> {
+        names
+            .iter()
+            .map(|name| {
+                term.vars()
+                    .iter()
+                    .find(|var| var.name() == *name)
+                    .map(|var| !var.is_negated())
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for PlaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PlaType::F => "f",
+            PlaType::FD => "fd",
+            PlaType::FR => "fr",
+            PlaType::FDR => "fdr",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::fmt::Display for Pla {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, ".i {}", self.inputs)?;
+        writeln!(f, ".o {}", self.outputs)?;
+        if let Some(labels) = &self.input_labels {
+            writeln!(f, ".ilb {}", labels.join(" "))?;
+        }
+        if let Some(labels) = &self.output_labels {
+            writeln!(f, ".ob {}", labels.join(" "))?;
+        }
+        writeln!(f, ".type {}", self.kind)?;
+        writeln!(f, ".p {}", self.cubes.len())?;
+
+        for cube in self.cubes.iter() {
+            for input in cube.inputs.iter() {
+                let c = match input {
+                    Some(false) => '0',
+                    Some(true) => '1',
+                    None => '-',
+                };
+                write!(f, "{}", c)?;
+            }
+            write!(f, " ")?;
+            for output in cube.outputs.iter() {
+                let c = match output {
+                    OutputValue::One => '1',
+                    OutputValue::Zero => '0',
+                    OutputValue::DontCare => '-',
+                    OutputValue::Unused => '~',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, ".e")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_header_and_cubes() {
+        let pla = Pla::parse(
+            "# comment\n.i 3\n.o 1\n.ilb a b c\n.ob f\n.type fd\n.p 2\n1-0 1\n011 -\n.e\n",
+        )
+        .unwrap();
+
+        assert_eq!(pla.inputs, 3);
+        assert_eq!(pla.outputs, 1);
+        assert_eq!(
+            pla.input_labels,
+            Some(vec!["a".into(), "b".into(), "c".into()])
+        );
+        assert_eq!(pla.kind, PlaType::FD);
+        assert_eq!(
+            pla.cubes[0],
+            Cube {
+                inputs: vec![Some(true), None, Some(false)],
+                outputs: vec![OutputValue::One],
+            }
+        );
+        assert_eq!(pla.cubes[1].outputs, vec![OutputValue::DontCare]);
+    }
+
+    #[test]
+    fn rejects_malformed_cubes() {
+        assert!(Pla::parse(".i 2\n.o 1\n1 1\n").is_err());
+        assert!(Pla::parse(".i 2\n.o 1\n1x 1\n").is_err());
+        assert!(Pla::parse("01 1\n").is_err());
+        // redeclaring the width after the cubes
+        assert!(Pla::parse(".i 2\n.o 1\n01 1\n.o 2\n").is_err());
+        assert!(Pla::parse(".i 2\n.o 1\n01 1\n.i 3\n").is_err());
+    }
+
+    #[test]
+    fn checks_the_width() {
+        assert!(Pla::parse(".i 25\n.o 1\n.type fr\n").is_err());
+
+        let mut pla = Pla::parse(".i 2\n.o 1\n01 1\n").unwrap();
+        assert!(pla.minterms(1).is_err());
+        pla.outputs = 2;
+        assert!(pla.minterms(1).is_err());
+    }
+
+    #[test]
+    fn minimize_uses_dont_cares() {
+        // f = x1 x2 + x1 ~x2 ~x3, with x1 ~x2 x3 free
+        let pla = Pla::parse(".i 3\n.o 1\n11- 1\n100 1\n101 -\n.e\n").unwrap();
+        let minimized = pla.minimize().unwrap();
+
+        assert_eq!(minimized.cubes.len(), 1);
+        assert_eq!(minimized.cubes[0].inputs, vec![Some(true), None, None]);
+        assert_eq!(
+            minimized.to_string(),
+            ".i 3\n.o 1\n.type f\n.p 1\n1-- 1\n.e\n"
+        );
+    }
+
+    #[test]
+    fn minimize_selects_a_cover() {
+        // the consensus cube -11 is prime, but not needed
+        let pla = Pla::parse(".i 3\n.o 1\n11- 1\n0-1 1\n.e\n").unwrap();
+        let minimized = pla.minimize().unwrap();
+
+        assert_eq!(minimized.cubes.len(), 2);
+        assert_eq!(
+            minimized.to_string(),
+            ".i 3\n.o 1\n.type f\n.p 2\n11- 1\n0-1 1\n.e\n"
+        );
+    }
+
+    #[test]
+    fn minimize_from_cubes() {
+        let pla = Pla::parse(".i 12\n.o 1\n------------ 1\n").unwrap();
+        assert_eq!(pla.minimize().unwrap().cubes[0].inputs, vec![None; 12]);
+
+        // off where x1 is 0 and x20 is 1, the rest of the 2^20 rows are free
+        let pla =
+            Pla::parse(".i 20\n.o 1\n.type fr\n11111111111111------ 1\n0------------------1 0\n")
+                .unwrap();
+        let minimized = pla.minimize().unwrap();
+        assert_eq!(minimized.cubes.len(), 1);
+        assert_eq!(minimized.cubes[0].inputs[0], Some(true));
+        assert_eq!(minimized.cubes[0].inputs[1..], vec![None; 19]);
+
+        // too many minterms to cover, and ON overlapping OFF
+        assert!(Pla::parse(".i 24\n.o 1\n------------------------ 1\n")
+            .unwrap()
+            .minimize()
+            .is_err());
+        assert!(Pla::parse(".i 2\n.o 1\n.type fr\n1- 1\n-1 0\n")
+            .unwrap()
+            .minimize()
+            .is_err());
+    }
+
+    #[test]
+    fn off_set_leaves_rest_free() {
+        let pla = Pla::parse(".i 2\n.o 1\n.type fr\n11 1\n00 0\n").unwrap();
+        let (on, dont_care) = pla.minterms(0).unwrap();
+        assert_eq!(on.len(), 1);
+        assert_eq!(dont_care.len(), 2);
+
+        let minimized = pla.minimize().unwrap();
+        assert_eq!(minimized.cubes.len(), 1);
+        assert_eq!(minimized.cubes[0].inputs.iter().flatten().count(), 1);
+    }
+
+    #[test]
+    fn outputs_share_rows() {
+        let pla = Pla::parse(".i 2\n.o 2\n11 11\n10 01\n").unwrap();
+        let minimized = pla.minimize().unwrap();
+
+        assert_eq!(
+            minimized.to_string(),
+            ".i 2\n.o 2\n.type f\n.p 2\n11 10\n1- 01\n.e\n"
+        );
+    }
+}
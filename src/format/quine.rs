@@ -0,0 +1,148 @@
+use crate::{
+    algorithm::mintermmini::MinimizeEvent,
+    format::notation::{display_width, Notation},
+    term::Term,
+    var::VarName,
+};
+
+/// The classic Quine–McCluskey tables, one per iteration: terms grouped by their number of
+/// positive literals, as cubes over `names` and marked when they got merged. Redundant terms
+/// are listed under the table of the iteration that found them.
+pub fn quine_tables(names: &[VarName], events: &[MinimizeEvent], notation: Notation) -> String {
+    let mark = match notation {
+        Notation::Ascii => "*",
+        Notation::Unicode | Notation::Latex => "✓",
+    };
+
+    let mut out = String::new();
+    let mut terms: Vec<Term> = vec![];
+    let mut merged: Vec<Term> = vec![];
+    let mut redundant: Vec<String> = vec![];
+
+    for event in events.iter() {
+        match event {
+            MinimizeEvent::IterationStart {
+                terms: iteration_terms,
+                ..
+            } => {
+                terms = iteration_terms.clone();
+                merged.clear();
+                redundant.clear();
+            }
+            MinimizeEvent::Merged { left, right, .. } => {
+                merged.push(left.clone());
+                merged.push(right.clone());
+            }
+            MinimizeEvent::Redundant {
+                redundant: term,
+                by,
+            } if term == by => redundant.push(format!("{} appears twice", notation.term(term))),
+            MinimizeEvent::Redundant {
+                redundant: term,
+                by,
+            } => redundant.push(format!(
+                "{} is covered by {}",
+                notation.term(term),
+                notation.term(by)
+            )),
+            MinimizeEvent::IterationEnd { iteration, merges } => {
+                out += &format!("Iteration {}, {} merges\n", iteration, merges);
+                out += &table(names, &terms, &merged, mark, notation);
+                for line in redundant.iter() {
+                    out += &format!("  {}\n", line);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// `1` for a positive literal, `0` for a negated one and `-` where the variable is missing
+pub fn cube(names: &[VarName], term: &Term) -> String {
+    names
+        .iter()
+        .map(
+            |name| match term.vars().iter().find(|var| var.name() == *name) {
+                None => '-',
+                Some(var) if var.is_negated() => '0',
+                Some(_) => '1',
+            },
+        )
+        .collect()
+}
+
+fn table(
+    names: &[VarName],
+    terms: &[Term],
+    merged: &[Term],
+    mark: &str,
+    notation: Notation,
+) -> String {
+    let ones = |term: &Term| term.vars().iter().filter(|var| !var.is_negated()).count();
+    let mut sorted: Vec<&Term> = terms.iter().collect();
+    sorted.sort_by_key(|term| ones(term));
+
+    let rows: Vec<(String, String, String, &str)> = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, term)| {
+            let group = if i == 0 || ones(sorted[i - 1]) != ones(term) {
+                ones(term).to_string()
+            } else {
+                String::new()
+            };
+            let is_merged = if merged.contains(term) { mark } else { "" };
+            (group, cube(names, term), notation.term(term), is_merged)
+        })
+        .collect();
+
+    let group_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+    let term_width = rows
+        .iter()
+        .map(|row| display_width(&row.2))
+        .max()
+        .unwrap_or(0);
+
+    rows.iter()
+        .map(|(group, cube, term, is_merged)| {
+            let line = format!(
+                "  {}{}  {}  {}{}  {}",
+                group,
+                " ".repeat(group_width - group.len()),
+                cube,
+                term,
+                " ".repeat(term_width - display_width(term)),
+                is_merged
+            );
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algorithm::mintermmini::{minimize_expression_observed, EventLog},
+        parser::grammar::parse,
+        traits::VarVisibility,
+    };
+
+    #[test]
+    fn tables() {
+        let exp = parse("~a ~b | ~a b | a b").unwrap();
+        let mut log = EventLog::default();
+        minimize_expression_observed(&exp, &mut log).unwrap();
+
+        assert_eq!(
+            quine_tables(&exp.get_sorted_variables(), &log.events, Notation::Ascii),
+            "Iteration 1, 2 merges\n\
+             \x20 0  00  ~a ~b  *\n\
+             \x20 1  01  ~a b   *\n\
+             \x20 2  11  a b    *\n\
+             Iteration 2, 0 merges\n\
+             \x20 0  0-  ~a\n\
+             \x20 1  -1  b\n"
+        );
+    }
+}
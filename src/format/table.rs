@@ -0,0 +1,120 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    format::notation::{display_width, Notation},
+    truthtable::TruthTable,
+    var::{Var, VarName},
+};
+
+/// Truth table of `exp` over its sorted variables, as aligned text or as a LaTeX `tabular`
+pub fn truth_table(
+    exp: &Expression,
+    function_name: &str,
+    notation: Notation,
+) -> Result<String, String> {
+    Ok(render_table(
+        &TruthTable::new_from_expression(exp)?,
+        function_name,
+        notation,
+    ))
+}
+
+/// Same as `truth_table` for a table that's already computed
+pub fn render_table(table: &TruthTable, function_name: &str, notation: Notation) -> String {
+    let names = table.names();
+
+    let headers: Vec<String> = names
+        .iter()
+        .map(|name| notation.var(&Var::new_from_name(*name, false)))
+        .collect();
+    let function = format!("{}({})", function_name, headers.join(", "));
+
+    let rows = (0..(1u64 << names.len())).map(|minterm| {
+        let mut row: Vec<char> = (0..names.len())
+            .map(|i| bit_char(minterm >> (names.len() - 1 - i)))
+            .collect();
+        row.push(bit_char((table.get(minterm) == Constant::ONE) as u64));
+        row
+    });
+
+    match notation {
+        Notation::Latex => latex_table(names, &headers, &function, rows),
+        Notation::Unicode | Notation::Ascii => text_table(&headers, &function, rows),
+    }
+}
+
+fn bit_char(bit: u64) -> char {
+    if bit & 1 == 1 {
+        '1'
+    } else {
+        '0'
+    }
+}
+
+fn text_table(headers: &[String], function: &str, rows: impl Iterator<Item = Vec<char>>) -> String {
+    let mut columns: Vec<&str> = headers.iter().map(String::as_str).collect();
+    columns.push(function);
+    let widths: Vec<usize> = columns.iter().map(|c| display_width(c)).collect();
+
+    let pad = |cells: Vec<String>| -> String {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - display_width(cell))))
+            .collect::<Vec<String>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut out = pad(columns.iter().map(|c| c.to_string()).collect()) + "\n";
+    for row in rows {
+        out += &pad(row.iter().map(|c| c.to_string()).collect());
+        out += "\n";
+    }
+    out
+}
+
+fn latex_table(
+    names: &[VarName],
+    headers: &[String],
+    function: &str,
+    rows: impl Iterator<Item = Vec<char>>,
+) -> String {
+    let mut out = format!("\\begin{{tabular}}{{{}|c}}\n", "c".repeat(names.len()));
+    let mut header_cells: Vec<String> = headers.iter().map(|h| format!("${}$", h)).collect();
+    header_cells.push(format!("${}$", function));
+    out += &format!("{} \\\\\n\\hline\n", header_cells.join(" & "));
+
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+        out += &format!("{} \\\\\n", cells.join(" & "));
+    }
+    out += "\\end{tabular}\n";
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::grammar::parse;
+
+    #[test]
+    fn text() {
+        let exp = parse("x1 ~x2").unwrap();
+        assert_eq!(
+            truth_table(&exp, "f", Notation::Ascii).unwrap(),
+            "x1 | x2 | f(x1, x2)\n0  | 0  | 0\n0  | 1  | 0\n1  | 0  | 1\n1  | 1  | 0\n"
+        );
+    }
+
+    #[test]
+    fn latex() {
+        let exp = parse("a | b").unwrap();
+        assert_eq!(
+            truth_table(&exp, "g", Notation::Latex).unwrap(),
+            "\\begin{tabular}{cc|c}\n$a$ & $b$ & $g(a, b)$ \\\\\n\\hline\n\
+             0 & 0 & 0 \\\\\n0 & 1 & 1 \\\\\n1 & 0 & 1 \\\\\n1 & 1 & 1 \\\\\n\\end{tabular}\n"
+        );
+    }
+}
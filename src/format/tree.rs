@@ -0,0 +1,78 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    var::Var,
+};
+
+/// Print the AST with every node on its own line and children indented by `indent` spaces
+/// under their parent. Terms are expanded into their literals and negated literals into a `~`
+/// node, so the output mirrors what the parser builds.
+pub fn print_ast(exp: &Expression, indent: usize) -> String {
+    let mut out = String::new();
+    write_node(exp, indent, 0, &mut out);
+    out
+}
+
+fn write_line(label: &str, indent: usize, depth: usize, out: &mut String) {
+    *out += &" ".repeat(indent * depth);
+    *out += label;
+    *out += "\n";
+}
+
+fn write_var(var: &Var, indent: usize, depth: usize, out: &mut String) {
+    if var.is_negated() {
+        write_line("~", indent, depth, out);
+        write_line(&var.name().get_identifier(), indent, depth + 1, out);
+    } else {
+        write_line(&var.name().get_identifier(), indent, depth, out);
+    }
+}
+
+fn write_node(exp: &Expression, indent: usize, depth: usize, out: &mut String) {
+    let (label, operands) = match exp {
+        Expression::Constant(Constant::ZERO) => return write_line("0", indent, depth, out),
+        Expression::Constant(Constant::ONE) => return write_line("1", indent, depth, out),
+        Expression::Var(var) => return write_var(var, indent, depth, out),
+        Expression::Term(term) => {
+            write_line("^", indent, depth, out);
+            for var in term.vars().iter() {
+                write_var(var, indent, depth + 1, out);
+            }
+            return;
+        }
+        Expression::Operation(Operation::NOT(Not(inner))) => {
+            ("~", std::slice::from_ref(inner.as_ref()))
+        }
+        Expression::Operation(Operation::AND(And(vecexp))) => ("^", vecexp.as_slice()),
+        Expression::Operation(Operation::OR(Or(vecexp))) => ("+", vecexp.as_slice()),
+        Expression::Operation(Operation::XOR(Xor(vecexp))) => ("⊕", vecexp.as_slice()),
+    };
+
+    write_line(label, indent, depth, out);
+    for operand in operands.iter() {
+        write_node(operand, indent, depth + 1, out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::grammar::parse;
+
+    #[test]
+    fn readme_example() {
+        let exp = parse("~x1 | ~x2 ~x3 x4").unwrap();
+        assert_eq!(
+            print_ast(&exp, 4),
+            "+\n    ~\n        x1\n    ^\n        ~\n            x2\n        ~\n            x3\n        x4\n"
+        );
+    }
+
+    #[test]
+    fn operations_and_indentation() {
+        let exp = parse("~(a ^ 1) & b").unwrap();
+        assert_eq!(print_ast(&exp, 2), "^\n  ~\n    ⊕\n      a\n      1\n  b\n");
+    }
+}
@@ -0,0 +1,9 @@
+pub mod algorithm;
+pub mod format;
+pub mod parser;
+pub mod types;
+
+#[cfg(test)]
+mod testing;
+
+pub use types::*;
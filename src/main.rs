@@ -1,73 +1,232 @@
-use std::env::args;
-mod algorithm;
-mod parser;
-mod types;
-
-use algorithm::mintermmini;
-use parser::{scanner::Scanner, tokenizer::Tokenizer};
-use term::Term;
-use types::*;
-
-fn print_terms(terms: &[Term]) {
-    for term in terms.iter() {
-        print!("{}, ", term);
+use std::{
+    env::args,
+    fs,
+    io::{stdin, BufRead},
+    process::ExitCode,
+};
+
+use logic::{
+    algorithm::{
+        esop::{compare_with_sop, minimize_esop},
+        implicates::{minimal_cnf, prime_implicates},
+        mintermmini::{minimize_expression, minimize_expression_observed, EventLog},
+        normalform::{clause_expression, cnf, dnf, nnf, MAX_NODES, MAX_TERMS},
+        rewrite::{size, Rewriter, Strategy},
+    },
+    format::{
+        derivation::derivation,
+        dot::{to_dot, DotOptions},
+        notation::{current_notation, set_notation, Notation},
+        pla::Pla,
+        quine::quine_tables,
+        table::truth_table,
+        tree::print_ast,
+    },
+    parser::grammar::parse,
+    traits::VarVisibility,
+};
+
+/// `.pla <input> [output]`, writes the minimized cover to output or stdout
+fn minimize_pla(cmd_args: &[String]) -> Result<(), String> {
+    let input = cmd_args.first().ok_or("Usage: .pla <input> [output]")?;
+    let source = fs::read_to_string(input).map_err(|e| format!("Can't read '{}': {}", input, e))?;
+
+    let minimized = Pla::parse(&source)?.minimize()?;
+
+    match cmd_args.get(1) {
+        None => print!("{}", minimized),
+        Some(output) => fs::write(output, minimized.to_string())
+            .map_err(|e| format!("Can't write '{}': {}", output, e))?,
     }
-    println!();
+    Ok(())
 }
 
-fn main() {
-    let cmd_args: Vec<String> = args().skip(1).collect();
-    let source = cmd_args.join("");
+/// `.dot [--share] <output> <expression>`
+fn write_dot(cmd_args: &[String]) -> Result<(), String> {
+    let usage = "Usage: .dot [--share] <output> <expression>";
+    let share_subtrees = cmd_args.first().map(String::as_str) == Some("--share");
+    let cmd_args = if share_subtrees {
+        &cmd_args[1..]
+    } else {
+        cmd_args
+    };
 
-    let mut sc = Scanner::new(source.as_str());
-    let lexemes = sc.scan();
+    let output = cmd_args.first().ok_or(usage)?;
+    if cmd_args.len() < 2 {
+        return Err(String::from(usage));
+    }
+    let exp = parse(&cmd_args[1..].join(" "))?;
+
+    let options = DotOptions {
+        share_subtrees,
+        ..Default::default()
+    };
+    fs::write(output, to_dot(&exp, &options))
+        .map_err(|e| format!("Can't write '{}': {}", output, e))
+}
 
-    if sc.has_errors() {
-        for error in sc.get_errors().iter() {
-            println!("Error: '{}'", error);
+/// `.printAST [--indent <n>] <expression>`
+fn print_tree(cmd_args: &[String]) -> Result<(), String> {
+    let usage = "Usage: .printAST [--indent <n>] <expression>";
+    let (indent, cmd_args) = match cmd_args.first().map(String::as_str) {
+        Some("--indent") => {
+            let indent = cmd_args
+                .get(1)
+                .and_then(|n| n.parse::<usize>().ok())
+                .ok_or(usage)?;
+            (indent, &cmd_args[2..])
         }
-        println!("Quitting parsing!");
+        _ => (4, cmd_args),
+    };
+    if cmd_args.is_empty() {
+        return Err(String::from(usage));
+    }
+
+    print!("{}", print_ast(&parse(&cmd_args.join(" "))?, indent));
+    Ok(())
+}
+
+/// `.table <expression>`
+fn print_table(cmd_args: &[String]) -> Result<(), String> {
+    let exp = parse(&cmd_args.join(" "))?;
+    print!("{}", truth_table(&exp, "f", current_notation())?);
+    Ok(())
+}
+
+/// `.minimize [--trace] <expression>`, the trace shows the Quine–McCluskey tables
+fn minimize(cmd_args: &[String]) -> Result<(), String> {
+    let trace = cmd_args.first().map(String::as_str) == Some("--trace");
+    let cmd_args = if trace { &cmd_args[1..] } else { cmd_args };
+    if cmd_args.is_empty() {
+        return Err(String::from("Usage: .minimize [--trace] <expression>"));
+    }
+
+    let exp = parse(&cmd_args.join(" "))?;
+    if trace {
+        let mut log = EventLog::default();
+        let minimized = minimize_expression_observed(&exp, &mut log)?;
+        let names = exp.get_sorted_variables();
+        print!("{}", quine_tables(&names, &log.events, current_notation()));
+        println!("{}", minimized);
+    } else {
+        println!("{}", minimize_expression(&exp)?);
     }
+    Ok(())
+}
 
-    println!("Scanner doesn't have errors, continuing parsing.");
+/// `.esop <expression>`, the XOR of products and how it compares to the minimal SOP
+fn esop(cmd_args: &[String]) -> Result<(), String> {
+    let exp = parse(&cmd_args.join(" "))?;
+    let esop = minimize_esop(&exp)?;
+    println!("{}", esop.to_expression());
+    println!("{}", compare_with_sop(&exp, &esop)?);
+    Ok(())
+}
+
+/// `.implicates [--minimal] <expression>`, every prime implicate on its own line or the
+/// minimal CNF made of them
+fn implicates(cmd_args: &[String]) -> Result<(), String> {
+    let minimal = cmd_args.first().map(String::as_str) == Some("--minimal");
+    let cmd_args = if minimal { &cmd_args[1..] } else { cmd_args };
+    if cmd_args.is_empty() {
+        return Err(String::from("Usage: .implicates [--minimal] <expression>"));
+    }
 
-    let mut tk = Tokenizer::new();
-    let tokens = tk.tokenize(lexemes, source.as_str());
-    if tk.has_errors() {
-        for error in tk.get_errors().iter() {
-            println!("Tokenizer error: '{}'", error);
+    let exp = parse(&cmd_args.join(" "))?;
+    if minimal {
+        println!("{}", minimal_cnf(&exp)?);
+    } else {
+        for clause in prime_implicates(&exp)? {
+            println!("{}", clause_expression(clause));
         }
     }
+    Ok(())
+}
+
+/// `.reduce [--steps] <expression>`, rewrites with the standard laws while that makes the
+/// expression smaller
+fn reduce(cmd_args: &[String]) -> Result<(), String> {
+    let steps = cmd_args.first().map(String::as_str) == Some("--steps");
+    let cmd_args = if steps { &cmd_args[1..] } else { cmd_args };
+    if cmd_args.is_empty() {
+        return Err(String::from("Usage: .reduce [--steps] <expression>"));
+    }
 
-    for token in tokens.iter() {
-        println!("Token '{:?}'", token);
+    let exp = parse(&cmd_args.join(" "))?;
+    let rewriter = Rewriter::standard();
+    if steps {
+        let steps = rewriter.rewrite_steps(&exp, Strategy::Fixpoint(size))?;
+        print!("{}", derivation(&exp, &steps, current_notation()));
+    } else {
+        println!("{}", rewriter.rewrite(&exp, Strategy::Fixpoint(size))?);
     }
+    Ok(())
+}
+
+fn switch_notation(notation: Notation, name: &str) -> Result<(), String> {
+    set_notation(notation);
+    println!("Output switched to {} expression", name);
+    Ok(())
+}
 
-    // for lex in leximes.into_iter() {
-    //     println!("Lex: '{}'", lex.str_from(string.as_str()));
-    // }
-
-    // println!(r#"argument structure: <number of variables> <minterm [minterm [...]]>"#);
-    // let n = cmd_args[1]
-    //     .parse::<i32>()
-    //     .expect("First argument must be <number of variables>");
-    // let max_minterm = (1 << n) - 1;
-    // let mut terms: Vec<Term> = vec![];
-    //
-    // for arg in cmd_args.iter().skip(2) {
-    //     let minterm: u64 = arg.parse::<u64>().expect("Minterm is not a number!");
-    //     if minterm > max_minterm {
-    //         eprintln!(
-    //             "Minterm {} cannot be represented with {} variables (0 <= m <= {})",
-    //             minterm, n, max_minterm
-    //         );
-    //         return;
-    //     }
-    //
-    //     let term = Term::new_from_minterm(n, minterm);
-    //     println!("Minterm {} -> {}", minterm, term);
-    //     terms.push(term);
-    // }
-    // print_terms(&terms);
-    // print_terms(&mintermmini::minimize(&terms));
+fn run(cmd_args: &[String]) -> Result<(), String> {
+    match cmd_args.first().map(String::as_str) {
+        Some(".pla") => minimize_pla(&cmd_args[1..]),
+        Some(".dot") => write_dot(&cmd_args[1..]),
+        Some(".printAST") => print_tree(&cmd_args[1..]),
+        Some(".table") => print_table(&cmd_args[1..]),
+        Some(".reduce") => reduce(&cmd_args[1..]),
+        Some(".nnf") => {
+            println!("{}", nnf(&parse(&cmd_args[1..].join(" "))?, MAX_NODES)?);
+            Ok(())
+        }
+        Some(".cnf") => {
+            println!("{}", cnf(&parse(&cmd_args[1..].join(" "))?, MAX_TERMS)?);
+            Ok(())
+        }
+        Some(".dnf") => {
+            println!("{}", dnf(&parse(&cmd_args[1..].join(" "))?, MAX_TERMS)?);
+            Ok(())
+        }
+        Some(".minimize") => minimize(&cmd_args[1..]),
+        Some(".esop") => esop(&cmd_args[1..]),
+        Some(".implicates") => implicates(&cmd_args[1..]),
+        Some(".displaylatex") => switch_notation(Notation::Latex, "latex"),
+        Some(".displayascii") => switch_notation(Notation::Ascii, "ascii"),
+        Some(".displayunicode") => switch_notation(Notation::Unicode, "unicode"),
+        Some(command) if command.starts_with('.') => Err(format!("Unknown command '{}'!", command)),
+        _ => parse(&cmd_args.join(" ")).map(|exp| println!("{}", exp)),
+    }
+}
+
+/// Fails if the command does, or with commands from stdin, if any of them did
+fn main() -> ExitCode {
+    let cmd_args: Vec<String> = args().skip(1).collect();
+
+    if !cmd_args.is_empty() {
+        return match run(&cmd_args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("Error: {}", error);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // without arguments, every line of stdin is a command
+    let mut status = ExitCode::SUCCESS;
+    for line in stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line_args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        if line_args.is_empty() {
+            continue;
+        }
+        if let Err(error) = run(&line_args) {
+            eprintln!("Error: {}", error);
+            status = ExitCode::FAILURE;
+        }
+    }
+    status
 }
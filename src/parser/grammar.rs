@@ -0,0 +1,290 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    term::Term,
+    traits::VariableLengthOperator,
+    var::{Var, VarName},
+};
+
+use super::{scanner::Scanner, tokenizer::Token, tokenizer::Tokenizer};
+
+/// Recursive descent over tokens. From the loosest to the tightest binding:
+/// `|`, `^`, `&` or concatenation of literals, `~`
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    errors: Vec<String>,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Parser {
+            tokens,
+            pos: 0,
+            errors: vec![],
+        }
+    }
+
+    pub fn parse(&mut self) -> Option<Expression> {
+        let exp = self.or()?;
+        if let Some(token) = self.tokens.get(self.pos) {
+            self.errors
+                .push(format!("Unexpected token '{:?}' after expression!", token));
+            return None;
+        }
+        Some(exp)
+    }
+
+    pub fn get_errors(&self) -> &[String] {
+        &self.errors
+    }
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next_if(&mut self, predicate: fn(&Token) -> bool) -> bool {
+        if self.peek().is_some_and(predicate) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Option<Expression> {
+        let mut operands = vec![self.xor()?];
+        while self.next_if(|t| matches!(t, Token::Pipe)) {
+            operands.push(self.xor()?);
+        }
+        Some(Self::collect::<Or>(operands))
+    }
+
+    fn xor(&mut self) -> Option<Expression> {
+        let mut operands = vec![self.and()?];
+        while self.next_if(|t| matches!(t, Token::Caret)) {
+            operands.push(self.and()?);
+        }
+        Some(Self::collect::<Xor>(operands))
+    }
+
+    fn and(&mut self) -> Option<Expression> {
+        let mut operands = vec![self.unary()?];
+        loop {
+            if self.next_if(|t| matches!(t, Token::Ampersand)) {
+                operands.push(self.unary()?);
+                continue;
+            }
+            if !self.peek().is_some_and(Self::starts_operand) {
+                break;
+            }
+
+            // concatenation, only literals may be written next to each other
+            let right = self.unary()?;
+            let left = operands.last().unwrap();
+            if !matches!(left, Expression::Var(_)) || !matches!(right, Expression::Var(_)) {
+                self.errors.push(String::from(
+                    "Only variables can be concatenated, use '&' for anything else!",
+                ));
+                return None;
+            }
+            operands.push(right);
+        }
+
+        if operands.len() > 1 && operands.iter().all(|e| matches!(e, Expression::Var(_))) {
+            let vars = operands
+                .into_iter()
+                .map(|e| match e {
+                    Expression::Var(var) => var,
+                    _ => unreachable!(),
+                })
+                .collect();
+            return Some(Expression::Term(Term::new_from_vars(vars)));
+        }
+        Some(Self::collect::<And>(operands))
+    }
+
+    fn unary(&mut self) -> Option<Expression> {
+        if self.next_if(|t| matches!(t, Token::Tilde)) {
+            return Some(match self.unary()? {
+                Expression::Constant(constant) => Expression::Constant(constant.negate()),
+                Expression::Var(var) => Expression::Var(var.negate()),
+                other => Expression::Operation(Operation::NOT(Not(Box::new(other)))),
+            });
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Option<Expression> {
+        let Some(token) = self.tokens.get(self.pos) else {
+            self.errors.push(String::from(
+                "Unexpected end of input, expected an operand!",
+            ));
+            return None;
+        };
+        self.pos += 1;
+
+        match token {
+            Token::C0 => Some(Expression::Constant(Constant::ZERO)),
+            Token::C1 => Some(Expression::Constant(Constant::ONE)),
+            Token::Identifier(name) => Some(Self::var(*name)),
+            Token::ParenL => self.group(|t| matches!(t, Token::ParenR), ')'),
+            Token::BracketL => self.group(|t| matches!(t, Token::BracketR), ']'),
+            other => {
+                self.errors.push(format!(
+                    "Unexpected token '{:?}', expected an operand!",
+                    other
+                ));
+                None
+            }
+        }
+    }
+
+    fn group(&mut self, is_closing: fn(&Token) -> bool, closing: char) -> Option<Expression> {
+        let exp = self.or()?;
+        if !self.next_if(is_closing) {
+            self.errors.push(format!("Missing closing '{}'!", closing));
+            return None;
+        }
+        Some(exp)
+    }
+
+    fn starts_operand(token: &Token) -> bool {
+        matches!(
+            token,
+            Token::Tilde
+                | Token::C0
+                | Token::C1
+                | Token::Identifier(_)
+                | Token::ParenL
+                | Token::BracketL
+        )
+    }
+
+    fn var(name: VarName) -> Expression {
+        Expression::Var(Var::new_from_name(name, false))
+    }
+
+    fn collect<O: VariableLengthOperator>(mut operands: Vec<Expression>) -> Expression {
+        if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            O::new(operands).into_expression()
+        }
+    }
+}
+
+/// Scan, tokenize and parse `source`, joining the errors of whichever stage failed
+pub fn parse(source: &str) -> Result<Expression, String> {
+    let mut scanner = Scanner::new(source);
+    let lexemes = scanner.scan();
+    if scanner.has_errors() {
+        return Err(scanner.get_errors().join("\n"));
+    }
+
+    let mut tokenizer = Tokenizer::new();
+    let tokens = tokenizer.tokenize(lexemes, source);
+    if tokenizer.has_errors() {
+        return Err(tokenizer.get_errors().join("\n"));
+    }
+
+    let mut parser = Parser::new(tokens);
+    match parser.parse() {
+        Some(exp) if !parser.has_errors() => Ok(exp),
+        _ => Err(parser.get_errors().join("\n")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn concatenated_literals_form_terms() {
+        let exp = parse("~x1 | ~x2 ~x3 x4").unwrap();
+        let Expression::Operation(Operation::OR(Or(operands))) = exp else {
+            panic!("Expected OR, got {:?}", exp);
+        };
+        assert!(matches!(operands[0], Expression::Var(v) if v == Var::new('x', Some(1), true)));
+        let Expression::Term(term) = &operands[1] else {
+            panic!("Expected a term, got {:?}", operands[1]);
+        };
+        assert_eq!(
+            term.vars(),
+            [
+                Var::new('x', Some(2), true),
+                Var::new('x', Some(3), true),
+                Var::new('x', Some(4), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        // a | (b ^ (c & ~(d | 0)))
+        let exp = parse("a | b ^ c & ~[d | 0]").unwrap();
+        let Expression::Operation(Operation::OR(Or(or))) = exp else {
+            panic!()
+        };
+        assert!(matches!(or[0], Expression::Var(_)));
+        let Expression::Operation(Operation::XOR(Xor(xor))) = &or[1] else {
+            panic!()
+        };
+        let Expression::Operation(Operation::AND(And(and))) = &xor[1] else {
+            panic!()
+        };
+        assert!(matches!(and[0], Expression::Var(v) if v == Var::new('c', None, false)));
+        assert!(matches!(
+            and[1],
+            Expression::Operation(Operation::NOT(Not(_)))
+        ));
+
+        assert!(matches!(
+            parse("~1").unwrap(),
+            Expression::Constant(Constant::ZERO)
+        ));
+        assert!(
+            matches!(parse("((~y))").unwrap(), Expression::Var(v) if v == Var::new('y', None, true))
+        );
+    }
+
+    #[test]
+    fn long_and_quoted_names() {
+        let exp = parse("enable ~rst_n | \"reset n\" & x300").unwrap();
+        let Expression::Operation(Operation::OR(Or(operands))) = exp else {
+            panic!("Expected OR, got {:?}", exp);
+        };
+        let Expression::Term(term) = &operands[0] else {
+            panic!("Expected a term, got {:?}", operands[0]);
+        };
+        assert_eq!(
+            term.vars(),
+            [
+                Var::new_from_name(VarName::new_from_identifier("enable"), false),
+                Var::new_from_name(VarName::new_from_identifier("rst_n"), true),
+            ]
+        );
+        let Expression::Term(term) = &operands[1] else {
+            panic!("Expected a term, got {:?}", operands[1]);
+        };
+        assert_eq!(term.vars()[0].name().identifier(), "reset n");
+        assert_eq!(term.vars()[1].name().subscript(), Some("300"));
+
+        assert!(parse("\"reset n").is_err());
+        assert!(parse("\"\" | a").is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse("a (b | c)").is_err());
+        assert!(parse("(a | b").is_err());
+        assert!(parse("a |").is_err());
+        assert!(parse("a b )").is_err());
+        assert!(parse("a2 & 7").is_err());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod grammar;
 pub mod lexeme;
 pub mod scanner;
 pub mod tokenizer;
@@ -1,18 +1,16 @@
-use std::{char, iter::Peekable, str::Chars};
+use std::{char, iter::Peekable, str::CharIndices};
 
 use super::lexeme::Lexeme;
 
 pub struct Scanner<'a> {
-    input: &'a str,
-    iter: Peekable<std::iter::Enumerate<Chars<'a>>>,
+    iter: Peekable<CharIndices<'a>>,
     errors: Vec<String>,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(input: &'a str) -> Scanner<'a> {
         Scanner {
-            input,
-            iter: input.chars().enumerate().peekable(),
+            iter: input.char_indices().peekable(),
             errors: vec![],
         }
     }
@@ -23,7 +21,6 @@ impl<'a> Scanner<'a> {
             if lex.is_empty() {
                 continue;
             }
-            println!("Lex: '{}'", lex.str_from(self.input));
             lexemes.push(lex);
         }
 
@@ -76,7 +73,11 @@ impl<'a> Scanner<'a> {
             return Some(Lexeme::new_empty(start));
         }
 
-        if !current.is_alphabetic() {
+        if current == '"' {
+            return Some(self.quoted(start));
+        }
+
+        if !current.is_alphabetic() && current != '_' {
             self.errors.push(format!(
                 "Character '{}' is neither whitespace, nor numeric!",
                 current
@@ -84,13 +85,29 @@ impl<'a> Scanner<'a> {
             return Some(Lexeme::new_empty(start));
         }
 
-        // must be alphabetic now
-
-        let mut len = 1;
-        while let Some((_, _)) = self.next_if(|c| c.is_numeric()) {
-            len += 1;
+        // identifier, lengths are in bytes like the start
+        let mut end = start + current.len_utf8();
+        while let Some((index, c)) = self.next_if(|c| c.is_alphanumeric() || c == '_') {
+            end = index + c.len_utf8();
         }
 
-        Some(Lexeme::new(start, len))
+        Some(Lexeme::new(start, end - start))
+    }
+
+    /// A name in double quotes, the quotes are part of the lexeme. A backslash escapes the
+    /// character after it, `VarName` reads the escapes.
+    fn quoted(&mut self, start: usize) -> Lexeme {
+        while let Some((index, c)) = self.iter.next() {
+            match c {
+                '"' => return Lexeme::new(start, index + 1 - start),
+                '\\' => {
+                    self.iter.next();
+                }
+                _ => (),
+            }
+        }
+        self.errors
+            .push(String::from("Quoted name is missing its closing '\"'!"));
+        Lexeme::new_empty(start)
     }
 }
@@ -1,5 +1,3 @@
-use std::u8;
-
 use crate::var::VarName;
 
 use super::lexeme::Lexeme;
@@ -8,7 +6,7 @@ pub struct Tokenizer {
     errors: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Token {
     ParenL,
     ParenR,
@@ -29,6 +27,12 @@ pub enum Token {
     Identifier(VarName),
 }
 
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Tokenizer {
     pub fn new() -> Self {
         Self { errors: vec![] }
@@ -37,7 +41,7 @@ impl Tokenizer {
     pub fn tokenize(&mut self, lexemes: Vec<Lexeme>, source: &str) -> Vec<Token> {
         let mut out = vec![];
         for lex in lexemes.iter() {
-            if let Some(token) = self.to_token(lex, source) {
+            if let Some(token) = self.lexeme_to_token(lex, source) {
                 out.push(token);
             }
         }
@@ -51,7 +55,7 @@ impl Tokenizer {
         !self.errors.is_empty()
     }
 
-    fn to_token(&mut self, lexeme: &Lexeme, source: &str) -> Option<Token> {
+    fn lexeme_to_token(&mut self, lexeme: &Lexeme, source: &str) -> Option<Token> {
         let str = lexeme.str_from(source);
 
         if str.is_empty() {
@@ -83,47 +87,15 @@ impl Tokenizer {
             return None;
         }
 
-        let mut iter = identifier.chars();
-
-        let name = iter.next().unwrap();
-
-        if !name.is_alphabetic() {
-            self.errors.push(format!(
-                "Tokenizer error: Lexeme '{:?}' representing '{}' is not a valid identifier!",
-                lexeme, identifier
-            ));
-            return None;
-        }
-
-        let mut has_any_digit = false;
-
-        let mut number = 0;
-        for digit in iter {
-            if !digit.is_numeric() {
+        match identifier.parse::<VarName>() {
+            Ok(name) => Some(Token::Identifier(name)),
+            Err(error) => {
                 self.errors.push(format!(
-                    "Tokenizer error: Lexeme '{:?}' representing '{}' does not have a valid number!",
-                    lexeme, identifier
+                    "Tokenizer error: Lexeme '{:?}' is not a valid identifier: {}",
+                    lexeme, error
                 ));
-                return None;
-            }
-            has_any_digit = true;
-
-            number = number * 10 + digit.to_digit(10).unwrap();
-        }
-
-        if number <= u8::MAX as u32 {
-            let subscript = if has_any_digit {
-                Some(number as u8)
-            } else {
                 None
-            };
-            return Some(Token::Identifier(VarName::new(name, subscript)));
+            }
         }
-
-        self.errors.push(format!(
-            "Tokenizer error: Lexeme '{:?}' representing '{}', an identifier has a subscript that can't fit into u8!",
-            lexeme, identifier
-        ));
-        None
     }
 }
@@ -0,0 +1,92 @@
+//! Helpers shared by the tests of several modules
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{And, Not, Or, Xor},
+    scope::VarScope,
+    term::Term,
+    traits::{Eval, VarVisibility},
+    var::Var,
+};
+
+/// Compares both on every row of the variables either one uses
+pub fn assert_equivalent(left: &Expression, right: &Expression) {
+    let mut names = left.get_sorted_variables();
+    names.extend(right.get_sorted_variables());
+    names.sort();
+    names.dedup();
+    for minterm in 0..(1u64 << names.len()) {
+        let scope = VarScope::new_from_minterm(&names, minterm);
+        assert_eq!(
+            left.evaluate(&scope),
+            right.evaluate(&scope),
+            "{} and {}",
+            left,
+            right
+        );
+    }
+}
+
+/// Tiny deterministic generator, so round trips and properties cover more shapes than
+/// written by hand
+pub struct Generator(pub u64);
+
+impl Generator {
+    pub fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+
+    pub fn var(&mut self) -> Var {
+        let name = ['a', 'b', 'x'][self.next(3) as usize];
+        let subscript = [None, Some(1), Some(12)][self.next(3) as usize];
+        Var::new(name, subscript, self.next(2) == 1)
+    }
+
+    fn operands(&mut self, depth: u32) -> Vec<Expression> {
+        (0..2 + self.next(2))
+            .map(|_| self.expression(depth - 1))
+            .collect()
+    }
+
+    /// Only shapes the parser builds: terms have at least two literals, NOT never wraps a
+    /// literal and AND always has an operand that isn't a plain variable
+    pub fn expression(&mut self, depth: u32) -> Expression {
+        let choice = if depth == 0 {
+            self.next(3)
+        } else {
+            self.next(7)
+        };
+        match choice {
+            0 => Expression::Constant(Constant::from(self.next(2) == 1)),
+            1 => Expression::Var(self.var()),
+            2 => Expression::Term(Term::new_from_vars(vec![self.var(), self.var()])),
+            3 => {
+                let inner = match self.expression(depth - 1) {
+                    inner @ (Expression::Constant(_) | Expression::Var(_)) => {
+                        Expression::Operation(Operation::OR(Or(vec![
+                            inner,
+                            Expression::Var(self.var()),
+                        ])))
+                    }
+                    inner => inner,
+                };
+                Expression::Operation(Operation::NOT(Not(Box::new(inner))))
+            }
+            4 => {
+                let mut operands = self.operands(depth);
+                if operands.iter().all(|e| matches!(e, Expression::Var(_))) {
+                    operands.push(Expression::Constant(Constant::ONE));
+                }
+                Expression::Operation(Operation::AND(And(operands)))
+            }
+            5 => Expression::Operation(Operation::OR(Or(self.operands(depth)))),
+            _ => Expression::Operation(Operation::XOR(Xor(self.operands(depth)))),
+        }
+    }
+}
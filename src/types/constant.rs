@@ -1,11 +1,13 @@
 use crate::{
     expression::Expression,
-    scope::VarScope,
-    traits::{Eval, Simplify, VarVisibility},
+    format::notation::current_notation,
+    scope::{VarPatterns, VarScope},
+    ternary::Ternary,
+    traits::{Eval, EvalBits, EvalTernary, Simplify, VarVisibility},
 };
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Constant {
     ZERO = 0,
     ONE = 1,
@@ -43,8 +45,14 @@ impl From<Constant> for bool {
     }
 }
 
+impl std::fmt::Display for Constant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", current_notation().constant(*self))
+    }
+}
+
 impl VarVisibility for Constant {
-    fn get_used_variables(&self, _varset: &mut std::collections::HashSet<crate::var::VarName>) {}
+    fn get_used_variables(&self, _varset: &mut crate::interner::VarSet) {}
 }
 
 impl Eval for Constant {
@@ -53,11 +61,23 @@ impl Eval for Constant {
     }
 }
 
-impl Simplify for Constant {
-    fn simplify(self) -> Expression {
-        Expression::Constant(self)
+impl EvalBits for Constant {
+    fn evaluate_bits(&self, _patterns: &VarPatterns) -> Result<u64, String> {
+        Ok(match self {
+            Constant::ZERO => 0,
+            Constant::ONE => !0,
+        })
     }
-    fn simplify_with(self, _scope: &VarScope) -> Expression {
+}
+
+impl EvalTernary for Constant {
+    fn evaluate_ternary(&self, _scope: &VarScope) -> Ternary {
+        Ternary::from(*self)
+    }
+}
+
+impl Simplify for Constant {
+    fn simplify_pass(self, _scope: Option<&VarScope>) -> Expression {
         Expression::Constant(self)
     }
 }
@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    operators::{absorb_by_parts, cancel_pairs, find_complements, And, Not, Or, Xor},
+    scope::VarScope,
+    term::Term,
+    traits::{Eval, Simplify},
+    var::Var,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+/// One node of an `ExpressionDag`, operands are other nodes of the same DAG
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Node {
+    Constant(Constant),
+    Var(Var),
+    Term(Term),
+    NOT(NodeId),
+    AND(Vec<NodeId>),
+    OR(Vec<NodeId>),
+    XOR(Vec<NodeId>),
+}
+
+/// Arena of hash-consed nodes, structurally equal subexpressions are stored once.
+/// Operands are always added before their parents, so a node only points to smaller ids.
+#[derive(Clone, Debug, Default)]
+pub struct ExpressionDag {
+    nodes: Vec<Node>,
+    ids: HashMap<Node, NodeId>,
+}
+
+impl ExpressionDag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    /// Returns the id of an equal node if there already is one
+    pub fn add(&mut self, node: Node) -> NodeId {
+        if let Some(id) = self.ids.get(&node) {
+            return *id;
+        }
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.ids.insert(node, id);
+        id
+    }
+
+    pub fn add_expression(&mut self, exp: &Expression) -> NodeId {
+        let node = match exp {
+            Expression::Constant(constant) => Node::Constant(*constant),
+            Expression::Var(var) => Node::Var(*var),
+            Expression::Term(term) => Node::Term(term.clone()),
+            Expression::Operation(Operation::NOT(Not(inner))) => {
+                Node::NOT(self.add_expression(inner))
+            }
+            Expression::Operation(Operation::AND(And(vecexp))) => Node::AND(self.add_all(vecexp)),
+            Expression::Operation(Operation::OR(Or(vecexp))) => Node::OR(self.add_all(vecexp)),
+            Expression::Operation(Operation::XOR(Xor(vecexp))) => Node::XOR(self.add_all(vecexp)),
+        };
+        self.add(node)
+    }
+
+    fn add_all(&mut self, vecexp: &[Expression]) -> Vec<NodeId> {
+        vecexp.iter().map(|exp| self.add_expression(exp)).collect()
+    }
+
+    /// Expand the node back into a tree, shared nodes get copied into every parent
+    pub fn to_expression(&self, id: NodeId) -> Expression {
+        let all = |ids: &[NodeId]| ids.iter().map(|id| self.to_expression(*id)).collect();
+        match self.node(id) {
+            Node::Constant(constant) => Expression::Constant(*constant),
+            Node::Var(var) => Expression::Var(*var),
+            Node::Term(term) => Expression::Term(term.clone()),
+            Node::NOT(inner) => {
+                Expression::Operation(Operation::NOT(Not(Box::new(self.to_expression(*inner)))))
+            }
+            Node::AND(ids) => Expression::Operation(Operation::AND(And(all(ids)))),
+            Node::OR(ids) => Expression::Operation(Operation::OR(Or(all(ids)))),
+            Node::XOR(ids) => Expression::Operation(Operation::XOR(Xor(all(ids)))),
+        }
+    }
+
+    /// Same as `Eval::evaluate` on the tree, but every shared node is evaluated only once
+    pub fn evaluate(&self, id: NodeId, scope: &VarScope) -> Result<Constant, String> {
+        self.evaluate_node(id, scope, &mut vec![None; self.nodes.len()])
+    }
+
+    fn evaluate_node(
+        &self,
+        id: NodeId,
+        scope: &VarScope,
+        cache: &mut Vec<Option<Constant>>,
+    ) -> Result<Constant, String> {
+        if let Some(value) = cache[id.0] {
+            return Ok(value);
+        }
+
+        let value = match self.node(id) {
+            Node::Constant(constant) => *constant,
+            Node::Var(var) => var.evaluate(scope)?,
+            Node::Term(term) => term.evaluate(scope)?,
+            Node::NOT(inner) => self.evaluate_node(*inner, scope, cache)?.negate(),
+            Node::AND(ids) => {
+                let mut value = Constant::ONE;
+                for id in ids.iter() {
+                    if self.evaluate_node(*id, scope, cache)? == Constant::ZERO {
+                        value = Constant::ZERO;
+                        break;
+                    }
+                }
+                value
+            }
+            Node::OR(ids) => {
+                let mut value = Constant::ZERO;
+                for id in ids.iter() {
+                    if self.evaluate_node(*id, scope, cache)? == Constant::ONE {
+                        value = Constant::ONE;
+                        break;
+                    }
+                }
+                value
+            }
+            Node::XOR(ids) => {
+                let mut value = Constant::ZERO;
+                for id in ids.iter() {
+                    if self.evaluate_node(*id, scope, cache)? == Constant::ONE {
+                        value = value.negate();
+                    }
+                }
+                value
+            }
+        };
+
+        cache[id.0] = Some(value);
+        Ok(value)
+    }
+
+    /// Same rules as `Simplify::simplify` on the tree, every shared node is simplified once
+    /// per pass
+    pub fn simplify(&mut self, id: NodeId) -> NodeId {
+        self.simplify_in(id, None)
+    }
+
+    /// Same rules as `Simplify::simplify_with` on the tree, every shared node is simplified
+    /// once per pass
+    pub fn simplify_with(&mut self, id: NodeId, scope: &VarScope) -> NodeId {
+        self.simplify_in(id, Some(scope))
+    }
+
+    /// Passes until a fixpoint, equal nodes share an id so comparing them is cheap
+    fn simplify_in(&mut self, id: NodeId, scope: Option<&VarScope>) -> NodeId {
+        let mut id = self.simplify_node(id, scope, &mut HashMap::new());
+        loop {
+            let next = self.simplify_node(id, scope, &mut HashMap::new());
+            if next == id {
+                return id;
+            }
+            id = next;
+        }
+    }
+
+    fn simplify_node(
+        &mut self,
+        id: NodeId,
+        scope: Option<&VarScope>,
+        cache: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if let Some(simplified) = cache.get(&id) {
+            return *simplified;
+        }
+
+        let simplified = match self.node(id).clone() {
+            Node::Constant(_) => id,
+            Node::Var(var) => {
+                let exp = var.simplify_pass(scope);
+                self.add_expression(&exp)
+            }
+            Node::Term(term) => {
+                let exp = term.simplify_pass(scope);
+                self.add_expression(&exp)
+            }
+            Node::NOT(inner) => {
+                let inner = self.simplify_node(inner, scope, cache);
+                self.negate(inner)
+            }
+            Node::AND(ids) => self.simplify_and_or(ids, Constant::ONE, scope, cache),
+            Node::OR(ids) => self.simplify_and_or(ids, Constant::ZERO, scope, cache),
+            Node::XOR(ids) => self.simplify_xor(ids, scope, cache),
+        };
+
+        cache.insert(id, simplified);
+        simplified
+    }
+
+    /// Like `Not::negate_simplified`, without leaving a double negation
+    fn negate(&mut self, id: NodeId) -> NodeId {
+        match self.node(id) {
+            Node::Constant(constant) => self.add(Node::Constant(constant.negate())),
+            Node::Var(var) => self.add(Node::Var(var.negate())),
+            Node::NOT(inner) => *inner,
+            _ => self.add(Node::NOT(id)),
+        }
+    }
+
+    /// Like `operators::is_complement`
+    fn is_complement(&self, left: NodeId, right: NodeId) -> bool {
+        match (self.node(left), self.node(right)) {
+            (Node::Var(left), Node::Var(right)) => left.is_dual(right),
+            (Node::NOT(inner), _) => *inner == right,
+            (_, Node::NOT(inner)) => *inner == left,
+            _ => false,
+        }
+    }
+
+    /// Like `And::conjuncts`
+    fn conjuncts(&mut self, id: NodeId) -> Vec<NodeId> {
+        match self.node(id).clone() {
+            Node::Term(term) => term
+                .vars()
+                .iter()
+                .map(|var| self.add(Node::Var(*var)))
+                .collect(),
+            Node::AND(ids) => ids.into_iter().flat_map(|id| self.conjuncts(id)).collect(),
+            _ => vec![id],
+        }
+    }
+
+    /// Like `Or::disjuncts`
+    fn disjuncts(&self, id: NodeId) -> Vec<NodeId> {
+        match self.node(id) {
+            Node::OR(ids) => ids.iter().flat_map(|id| self.disjuncts(*id)).collect(),
+            _ => vec![id],
+        }
+    }
+
+    /// Same absorption as the tree, for AND when `identity` is ONE and for OR otherwise
+    fn absorb(&mut self, operands: Vec<NodeId>, identity: Constant) -> Vec<NodeId> {
+        let parts: Vec<Vec<NodeId>> = operands
+            .iter()
+            .map(|id| match identity {
+                Constant::ONE => self.disjuncts(*id),
+                Constant::ZERO => self.conjuncts(*id),
+            })
+            .collect();
+        absorb_by_parts(operands, &parts)
+    }
+
+    /// Like `And::regroup_operands`
+    fn regroup_literals(&mut self, operands: Vec<NodeId>) -> Vec<NodeId> {
+        let (literals, mut others): (Vec<NodeId>, Vec<NodeId>) = operands
+            .into_iter()
+            .partition(|id| matches!(self.node(*id), Node::Var(_)));
+        if literals.len() < 2 {
+            others.extend(literals);
+        } else {
+            let vars = literals
+                .iter()
+                .filter_map(|id| match self.node(*id) {
+                    Node::Var(var) => Some(*var),
+                    _ => None,
+                })
+                .collect();
+            others.push(self.add(Node::Term(Term::new_from_vars(vars))));
+        }
+        others.sort();
+        others
+    }
+
+    /// AND when `identity` is ONE, OR when it is ZERO
+    fn simplify_and_or(
+        &mut self,
+        ids: Vec<NodeId>,
+        identity: Constant,
+        scope: Option<&VarScope>,
+        cache: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        let mut operands: Vec<NodeId> = vec![];
+
+        for id in ids.into_iter() {
+            let simplified = self.simplify_node(id, scope, cache);
+            match (self.node(simplified), identity) {
+                (Node::Constant(constant), _) if *constant == identity => continue,
+                (Node::Constant(_), _) => return simplified,
+                (Node::AND(inner), Constant::ONE) | (Node::OR(inner), Constant::ZERO) => {
+                    operands.extend(inner.iter())
+                }
+                _ => operands.push(simplified),
+            }
+        }
+
+        // like `And::split_operand`, terms take part literal by literal
+        let split = identity == Constant::ONE
+            && operands
+                .iter()
+                .any(|id| matches!(self.node(*id), Node::Term(_)));
+        if split {
+            operands = operands
+                .into_iter()
+                .flat_map(|id| self.conjuncts(id))
+                .collect();
+        }
+
+        // sorted like the tree operands, so commuted operations share a node
+        operands.sort();
+        operands.dedup();
+        if find_complements(&operands, |a, b| self.is_complement(*a, *b)).is_some() {
+            // x & ~x = 0, x | ~x = 1
+            return self.add(Node::Constant(identity.negate()));
+        }
+        let mut operands = self.absorb(operands, identity);
+        if split {
+            operands = self.regroup_literals(operands);
+        }
+
+        match operands.len() {
+            0 => self.add(Node::Constant(identity)),
+            1 => operands[0],
+            _ if identity == Constant::ONE => self.add(Node::AND(operands)),
+            _ => self.add(Node::OR(operands)),
+        }
+    }
+
+    fn simplify_xor(
+        &mut self,
+        ids: Vec<NodeId>,
+        scope: Option<&VarScope>,
+        cache: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        let mut operands: Vec<NodeId> = vec![];
+        let mut negate_result = false;
+
+        for id in ids.into_iter() {
+            let simplified = self.simplify_node(id, scope, cache);
+            match self.node(simplified) {
+                Node::Constant(Constant::ONE) => negate_result = !negate_result,
+                Node::Constant(Constant::ZERO) => continue,
+                Node::XOR(inner) => operands.extend(inner.iter()),
+                _ => operands.push(simplified),
+            }
+        }
+
+        operands.sort();
+        // x ^ x = 0
+        let mut operands = cancel_pairs(operands);
+        // x ^ ~x = 1
+        while let Some((i, j)) = find_complements(&operands, |a, b| self.is_complement(*a, *b)) {
+            negate_result = !negate_result;
+            operands.remove(j);
+            operands.remove(i);
+        }
+
+        let result = match operands.len() {
+            0 => return self.add(Node::Constant(Constant::ZERO.negate_if(negate_result))),
+            1 => operands[0],
+            _ => self.add(Node::XOR(operands)),
+        };
+        if negate_result {
+            self.negate(result)
+        } else {
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::grammar::parse, scope::VarValue, testing::Generator, var::VarName};
+
+    #[test]
+    fn shares_subexpressions() {
+        let exp = parse("(a | b c) & ~(a | b c) ^ (a | b c)").unwrap();
+        let mut dag = ExpressionDag::new();
+        let root = dag.add_expression(&exp);
+
+        // a, b c, the OR, the NOT, the AND and the XOR
+        assert_eq!(dag.len(), 6);
+        assert_eq!(dag.add_expression(&parse("a | b c").unwrap()), NodeId(2));
+        assert_eq!(dag.to_expression(root), exp);
+    }
+
+    #[test]
+    fn evaluates_like_the_tree() {
+        let exp = parse("(a | ~b c) & ~(a | ~b c) ^ (a ^ 1) | c").unwrap();
+        let mut dag = ExpressionDag::new();
+        let root = dag.add_expression(&exp);
+        let names = [
+            VarName::new('a', None),
+            VarName::new('b', None),
+            VarName::new('c', None),
+        ];
+
+        for minterm in 0..8 {
+            let scope = VarValue::new_from_minterm(&names, minterm);
+            assert_eq!(dag.evaluate(root, &scope), exp.evaluate(&scope));
+        }
+    }
+
+    #[test]
+    fn simplifies_like_the_tree() {
+        let scope: VarValue<Constant> = [(VarName::new('b', None), Constant::ZERO)]
+            .into_iter()
+            .collect();
+
+        for source in [
+            "(a & 1) & (c & (a | 0))",
+            "~~(a | b) ^ 1 ^ (c ^ a)",
+            "(a a ~c) | (0 & b) | ~(1 ^ 1)",
+            "~(a | b) & ~(a | b) & (b ^ 1)",
+            "(a | ~a) & (c ^ c ^ a) | a x1",
+            "~(a b) ^ (a b) ^ ~~c & (c | x1)",
+        ] {
+            let exp = parse(source).unwrap();
+            let mut dag = ExpressionDag::new();
+            let root = dag.add_expression(&exp);
+
+            let simplified = dag.simplify(root);
+            assert_eq!(
+                dag.to_expression(simplified).canonicalize(),
+                exp.clone().simplify()
+            );
+            let simplified = dag.simplify_with(root, &scope);
+            assert_eq!(
+                dag.to_expression(simplified).canonicalize(),
+                exp.simplify_with(&scope)
+            );
+        }
+    }
+
+    #[test]
+    fn simplifies_random_expressions_like_the_tree() {
+        let scope: VarValue<Constant> = [(VarName::new('a', None), Constant::ONE)]
+            .into_iter()
+            .collect();
+
+        let mut generator = Generator(0xda9);
+        for _ in 0..500 {
+            let exp = generator.expression(4);
+            let mut dag = ExpressionDag::new();
+            let root = dag.add_expression(&exp);
+
+            let simplified = dag.simplify(root);
+            assert_eq!(
+                dag.to_expression(simplified).canonicalize(),
+                exp.clone().simplify(),
+                "from {:?}",
+                exp
+            );
+            let simplified = dag.simplify_with(root, &scope);
+            assert_eq!(
+                dag.to_expression(simplified).canonicalize(),
+                exp.clone().simplify_with(&scope),
+                "from {:?}",
+                exp
+            );
+        }
+    }
+}
@@ -1,13 +1,18 @@
 use crate::{
     constant::Constant,
+    format::notation::current_notation,
     operation::Operation,
-    scope::VarScope,
+    operators::{And, Not, Or, Xor},
+    scope::{VarPatterns, VarScope},
     term::Term,
-    traits::{Eval, Simplify, VarVisibility},
+    ternary::Ternary,
+    traits::{Eval, EvalBits, EvalTernary, Operator, Simplify, VarVisibility},
     var::Var,
 };
 
-#[derive(Clone, Debug)]
+/// Ordered constants < variables < terms < operations, with operations ordered by their
+/// operator first and their operands second
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Expression {
     Constant(Constant),
     Var(Var),
@@ -15,27 +20,70 @@ pub enum Expression {
     Operation(Operation),
 }
 
-impl Simplify for Expression {
-    fn simplify(self) -> Expression {
+impl Expression {
+    /// Sum of products, the OR of the terms
+    pub fn new_from_terms(terms: Vec<Term>) -> Expression {
+        let mut products: Vec<Expression> = terms
+            .into_iter()
+            .map(|term| match term.vars() {
+                [] => Expression::Constant(Constant::ONE),
+                [var] => Expression::Var(*var),
+                _ => Expression::Term(term),
+            })
+            .collect();
+
+        match products.len() {
+            0 => Expression::Constant(Constant::ZERO),
+            1 => products.pop().unwrap(),
+            _ => Expression::Operation(Operation::OR(Or(products))),
+        }
+    }
+}
+
+impl Expression {
+    /// Sort the operands of every commutative operation, so formulas that only differ in the
+    /// order of operands become equal
+    pub fn canonicalize(self) -> Expression {
         match self {
-            Expression::Var(data) => data.simplify(),
-            Expression::Constant(data) => data.simplify(),
-            Expression::Term(data) => data.simplify(),
-            Expression::Operation(data) => data.simplify(),
+            Expression::Operation(operation) => {
+                let is_commutative = operation.is_commutative();
+                let sort = |vecexp: Vec<Expression>| -> Vec<Expression> {
+                    let mut vecexp: Vec<Expression> =
+                        vecexp.into_iter().map(Expression::canonicalize).collect();
+                    if is_commutative {
+                        vecexp.sort();
+                    }
+                    vecexp
+                };
+
+                Expression::Operation(match operation {
+                    Operation::NOT(Not(inner)) => {
+                        Operation::NOT(Not(Box::new(inner.canonicalize())))
+                    }
+                    Operation::AND(And(vecexp)) => Operation::AND(And(sort(vecexp))),
+                    Operation::OR(Or(vecexp)) => Operation::OR(Or(sort(vecexp))),
+                    Operation::XOR(Xor(vecexp)) => Operation::XOR(Xor(sort(vecexp))),
+                })
+            }
+            // terms keep their variables sorted already
+            other => other,
         }
     }
-    fn simplify_with(self, scope: &VarScope) -> Expression {
+}
+
+impl Simplify for Expression {
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression {
         match self {
-            Expression::Var(data) => data.simplify_with(scope),
-            Expression::Constant(data) => data.simplify_with(scope),
-            Expression::Term(data) => data.simplify_with(scope),
-            Expression::Operation(data) => data.simplify_with(scope),
+            Expression::Var(data) => data.simplify_pass(scope),
+            Expression::Constant(data) => data.simplify_pass(scope),
+            Expression::Term(data) => data.simplify_pass(scope),
+            Expression::Operation(data) => data.simplify_pass(scope),
         }
     }
 }
 
 impl VarVisibility for Expression {
-    fn get_used_variables(&self, varset: &mut std::collections::HashSet<crate::var::VarName>) {
+    fn get_used_variables(&self, varset: &mut crate::interner::VarSet) {
         match self {
             Expression::Var(data) => data.get_used_variables(varset),
             Expression::Constant(data) => data.get_used_variables(varset),
@@ -55,3 +103,112 @@ impl Eval for Expression {
         }
     }
 }
+
+impl EvalBits for Expression {
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String> {
+        match self {
+            Expression::Var(data) => data.evaluate_bits(patterns),
+            Expression::Constant(data) => data.evaluate_bits(patterns),
+            Expression::Term(data) => data.evaluate_bits(patterns),
+            Expression::Operation(data) => data.evaluate_bits(patterns),
+        }
+    }
+}
+
+impl EvalTernary for Expression {
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary {
+        match self {
+            Expression::Var(data) => data.evaluate_ternary(scope),
+            Expression::Constant(data) => data.evaluate_ternary(scope),
+            Expression::Term(data) => data.evaluate_ternary(scope),
+            Expression::Operation(data) => data.evaluate_ternary(scope),
+        }
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", current_notation().expression(self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::grammar::parse,
+        scope::VarValue,
+        testing::{assert_equivalent, Generator},
+        var::VarName,
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn canonical_ordering() {
+        let a = parse("(c | b a) & ~(x1 ^ 1 ^ a) & 0")
+            .unwrap()
+            .canonicalize();
+        let b = parse("0 & ~(a ^ x1 ^ 1) & (a b | c)")
+            .unwrap()
+            .canonicalize();
+        assert_eq!(a, b);
+
+        // constants < variables < terms < operations
+        let Expression::Operation(Operation::AND(And(operands))) = a else {
+            panic!("Expected AND, got {:?}", a);
+        };
+        assert!(matches!(operands[0], Expression::Constant(Constant::ZERO)));
+        assert!(matches!(
+            operands[1],
+            Expression::Operation(Operation::NOT(Not(_)))
+        ));
+        assert!(matches!(
+            operands[2],
+            Expression::Operation(Operation::OR(Or(_)))
+        ));
+    }
+
+    #[test]
+    fn hashing() {
+        let set: HashSet<Expression> = ["a | b", "b | a", "a | b", "a b"]
+            .iter()
+            .map(|source| parse(source).unwrap().canonicalize())
+            .collect();
+        assert_eq!(set.len(), 2);
+        assert_ne!(parse("a | b").unwrap(), parse("b | a").unwrap());
+    }
+
+    #[test]
+    fn simplify_is_idempotent() {
+        let mut generator = Generator(0x1de);
+        for _ in 0..500 {
+            let exp = generator.expression(4);
+            let simplified = exp.clone().simplify();
+            assert_eq!(simplified.clone().simplify(), simplified, "from {:?}", exp);
+            assert_equivalent(&exp, &simplified);
+        }
+    }
+
+    #[test]
+    fn simplify_with_is_idempotent() {
+        let scope: VarValue<Constant> = [
+            (VarName::new('a', None), Constant::ONE),
+            (VarName::new('x', Some(1)), Constant::ZERO),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut generator = Generator(0x5c0);
+        for _ in 0..500 {
+            let exp = generator.expression(4);
+            let simplified = exp.clone().simplify_with(&scope);
+            assert_eq!(
+                simplified.clone().simplify_with(&scope),
+                simplified,
+                "from {:?}",
+                exp
+            );
+            assert_eq!(simplified.clone().simplify(), simplified, "from {:?}", exp);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+use crate::var::VarName;
+
+/// Gives every identifier a dense index, in the order they are first seen. `VarName` keeps
+/// its index, so sets of variables can be bitsets.
+///
+/// Identifiers are leaked and never freed, so the interner grows with every distinct name the
+/// process has seen, a few bytes each. In the REPL that's the names typed in, generated names
+/// all come from the cached x₁..xₙ.
+#[derive(Debug, Default)]
+pub struct VarInterner {
+    identifiers: Vec<&'static str>,
+    indices: HashMap<&'static str, usize>,
+    /// indices of x1, x2, ..., see `numbered`
+    numbered: Vec<usize>,
+}
+
+impl VarInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The one every `VarName` is interned in
+    pub fn global() -> MutexGuard<'static, VarInterner> {
+        static INTERNER: OnceLock<Mutex<VarInterner>> = OnceLock::new();
+        INTERNER.get_or_init(Default::default).lock().unwrap()
+    }
+
+    /// Index of `identifier`, adding it if it's new. Every distinct identifier is leaked once,
+    /// there are only ever so many of them.
+    pub fn intern(&mut self, identifier: &str) -> (usize, &'static str) {
+        if let Some(index) = self.indices.get(identifier) {
+            return (*index, self.identifiers[*index]);
+        }
+        let leaked: &'static str = Box::leak(identifier.to_string().into_boxed_str());
+        let index = self.identifiers.len();
+        self.identifiers.push(leaked);
+        self.indices.insert(leaked, index);
+        (index, leaked)
+    }
+
+    /// Indices of x1..x`count`, only formatted and hashed the first time they are asked for
+    pub fn numbered(&mut self, count: usize) -> &[usize] {
+        while self.numbered.len() < count {
+            let (index, _) = self.intern(&format!("x{}", self.numbered.len() + 1));
+            self.numbered.push(index);
+        }
+        &self.numbered[..count]
+    }
+
+    pub fn index_of(&self, identifier: &str) -> Option<usize> {
+        self.indices.get(identifier).copied()
+    }
+    pub fn identifier(&self, index: usize) -> Option<&'static str> {
+        self.identifiers.get(index).copied()
+    }
+    pub fn len(&self) -> usize {
+        self.identifiers.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.identifiers.is_empty()
+    }
+}
+
+/// Set of variables as a bitset over their indices
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VarSet {
+    bits: Vec<u64>,
+}
+
+impl VarSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether `name` wasn't in the set yet
+    pub fn insert(&mut self, name: VarName) -> bool {
+        let (word, bit) = (name.index() / 64, 1 << (name.index() % 64));
+        if self.bits.len() <= word {
+            self.bits.resize(word + 1, 0);
+        }
+        let is_new = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        is_new
+    }
+    pub fn contains(&self, name: &VarName) -> bool {
+        self.bits
+            .get(name.index() / 64)
+            .is_some_and(|word| word & (1 << (name.index() % 64)) != 0)
+    }
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|word| *word == 0)
+    }
+
+    /// In the order of indices, not the natural order of names
+    pub fn iter(&self) -> impl Iterator<Item = VarName> + '_ {
+        let interner = VarInterner::global();
+        let names: Vec<VarName> = (0..self.bits.len() * 64)
+            .filter(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+            .map(|index| VarName::new_from_index(index, interner.identifier(index).unwrap()))
+            .collect();
+        names.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dense_indices() {
+        let mut interner = VarInterner::new();
+        assert_eq!(interner.intern("a").0, 0);
+        assert_eq!(interner.intern("rst_n").0, 1);
+        assert_eq!(interner.intern("a").0, 0);
+        assert_eq!(interner.index_of("rst_n"), Some(1));
+        assert_eq!(interner.identifier(1), Some("rst_n"));
+        assert_eq!(interner.len(), 2);
+
+        let numbered = interner.numbered(3).to_vec();
+        assert_eq!(numbered, [2, 3, 4]);
+        assert_eq!(interner.identifier(numbered[2]), Some("x3"));
+        assert_eq!(interner.numbered(2), [2, 3]);
+        assert_eq!(interner.len(), 5);
+    }
+
+    #[test]
+    fn bitset() {
+        let names = ["a", "x300", "a"].map(VarName::new_from_identifier);
+        let mut set = VarSet::new();
+        assert!(set.insert(names[0]));
+        assert!(set.insert(names[1]));
+        assert!(!set.insert(names[2]));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&names[1]));
+        assert!(!set.contains(&VarName::new_from_identifier("b")));
+
+        let mut found: Vec<VarName> = set.iter().collect();
+        found.sort();
+        assert_eq!(found, names[..2]);
+    }
+}
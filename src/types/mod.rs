@@ -1,7 +1,12 @@
 pub mod constant;
+pub mod dag;
 pub mod expression;
+pub mod interner;
 pub mod operation;
+pub mod operators;
 pub mod scope;
 pub mod term;
+pub mod ternary;
 pub mod traits;
+pub mod truthtable;
 pub mod var;
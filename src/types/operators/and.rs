@@ -0,0 +1,156 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    scope::{VarPatterns, VarScope},
+    term::Term,
+    ternary::Ternary,
+    traits::{
+        ConstantBehavior, DuplicateBehavior, Eval, EvalBits, EvalTernary, Operator, Simplify,
+        VarVisibility, VariableLengthOperator,
+    },
+};
+
+use super::{simplify_operands, Or};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct And(pub Vec<Expression>);
+
+impl And {
+    /// The literals and other operands `exp` is the AND of, terms count as AND too
+    pub fn conjuncts(exp: &Expression) -> Vec<Expression> {
+        match exp {
+            Expression::Term(term) => term
+                .vars()
+                .iter()
+                .map(|var| Expression::Var(*var))
+                .collect(),
+            Expression::Operation(Operation::AND(And(vecexp))) => {
+                vecexp.iter().flat_map(Self::conjuncts).collect()
+            }
+            other => vec![other.clone()],
+        }
+    }
+}
+
+impl VariableLengthOperator for And {
+    fn new(operands: Vec<Expression>) -> Self {
+        And(operands)
+    }
+    fn operands(&self) -> &[Expression] {
+        &self.0
+    }
+    fn into_operands(self) -> Vec<Expression> {
+        self.0
+    }
+
+    fn into_expression(self) -> Expression {
+        Expression::Operation(Operation::AND(self))
+    }
+    fn try_from_expression(exp: Expression) -> Result<Self, Expression> {
+        match exp {
+            Expression::Operation(Operation::AND(inner)) => Ok(inner),
+            other => Err(other),
+        }
+    }
+
+    fn zero_args_behavior() -> Constant {
+        Constant::ONE
+    }
+    fn constant_behavior(constant: Constant) -> ConstantBehavior {
+        match constant {
+            Constant::ONE => ConstantBehavior::Skip,
+            Constant::ZERO => ConstantBehavior::Dominate,
+        }
+    }
+
+    fn duplicate_behavior() -> DuplicateBehavior {
+        DuplicateBehavior::Merge
+    }
+    fn complement_value() -> Constant {
+        Constant::ZERO
+    }
+    fn absorption_parts(exp: &Expression) -> Option<Vec<Expression>> {
+        Some(Or::disjuncts(exp))
+    }
+    fn split_operand(exp: Expression) -> Result<Vec<Expression>, Expression> {
+        match exp {
+            Expression::Term(_) => Ok(Self::conjuncts(&exp)),
+            other => Err(other),
+        }
+    }
+    /// The literals left over become one term again
+    fn regroup_operands(operands: Vec<Expression>) -> Vec<Expression> {
+        let (literals, mut others): (Vec<Expression>, Vec<Expression>) = operands
+            .into_iter()
+            .partition(|exp| matches!(exp, Expression::Var(_)));
+        if literals.len() < 2 {
+            others.extend(literals);
+            return others;
+        }
+        let vars = literals
+            .into_iter()
+            .filter_map(|exp| match exp {
+                Expression::Var(var) => Some(var),
+                _ => None,
+            })
+            .collect();
+        others.push(Expression::Term(Term::new_from_vars(vars)));
+        others
+    }
+}
+
+impl Operator for And {
+    fn is_commutative(&self) -> bool {
+        true
+    }
+    fn is_associative(&self) -> bool {
+        true
+    }
+}
+
+impl VarVisibility for And {
+    fn get_used_variables(&self, varset: &mut crate::interner::VarSet) {
+        self.0.iter().for_each(|exp| exp.get_used_variables(varset))
+    }
+}
+
+impl EvalBits for And {
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String> {
+        self.0
+            .iter()
+            .try_fold(!0, |rows, exp| Ok(rows & exp.evaluate_bits(patterns)?))
+    }
+}
+
+impl EvalTernary for And {
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary {
+        let mut result = Ternary::ONE;
+        for exp in self.0.iter() {
+            match exp.evaluate_ternary(scope) {
+                // a single 0 decides, even after unknown operands
+                Ternary::ZERO => return Ternary::ZERO,
+                value => result = result.and(value),
+            }
+        }
+        result
+    }
+}
+
+impl Simplify for And {
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression {
+        simplify_operands(self, scope)
+    }
+}
+
+impl Eval for And {
+    fn evaluate(&self, scope: &VarScope) -> Result<Constant, String> {
+        for exp in self.0.iter() {
+            match exp.evaluate(scope)? {
+                Constant::ZERO => return Ok(Constant::ZERO),
+                Constant::ONE => continue,
+            }
+        }
+        Ok(Constant::ONE)
+    }
+}
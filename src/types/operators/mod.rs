@@ -0,0 +1,236 @@
+pub mod and;
+pub mod not;
+pub mod or;
+pub mod xor;
+
+pub use and::And;
+pub use not::Not;
+pub use or::Or;
+pub use xor::Xor;
+
+use crate::{
+    expression::Expression,
+    operation::Operation,
+    scope::VarScope,
+    traits::{ConstantBehavior, DuplicateBehavior, Simplify, VariableLengthOperator},
+};
+
+/// x and ~x, for variables and for a negation of any other expression
+pub fn is_complement(left: &Expression, right: &Expression) -> bool {
+    match (left, right) {
+        (Expression::Var(left), Expression::Var(right)) => left.is_dual(right),
+        (Expression::Operation(Operation::NOT(Not(inner))), other)
+        | (other, Expression::Operation(Operation::NOT(Not(inner)))) => **inner == *other,
+        _ => false,
+    }
+}
+
+/// Shared by every variable length operator: simplify each operand, fold constants, flatten
+/// nested uses of the same operator if it is associative and sort operands if it is
+/// commutative. Sorted operands then get merged or cancelled with their duplicates and
+/// complements, and absorbed by their parts. Operands that split, like terms under AND, take
+/// part one by one and are put back together at the end.
+pub fn simplify_operands<O: VariableLengthOperator>(
+    operator: O,
+    scope: Option<&VarScope>,
+) -> Expression {
+    let is_associative = operator.is_associative();
+    let is_commutative = operator.is_commutative();
+
+    let mut new_exp: Vec<Expression> = vec![];
+    let mut negate_result = false;
+    let mut split = false;
+
+    for exp in operator.into_operands().into_iter() {
+        match exp.simplify_pass(scope) {
+            Expression::Constant(constant) => match O::constant_behavior(constant) {
+                ConstantBehavior::Skip => continue,
+                ConstantBehavior::Dominate => return Expression::Constant(constant),
+                ConstantBehavior::Negate => negate_result = !negate_result,
+            },
+            other => {
+                let operands = match O::try_from_expression(other) {
+                    Ok(inner) if is_associative => inner.into_operands(),
+                    Ok(inner) => vec![inner.into_expression()],
+                    Err(other) => vec![other],
+                };
+                for operand in operands {
+                    match O::split_operand(operand) {
+                        Ok(mut parts) => {
+                            new_exp.append(&mut parts);
+                            split = true;
+                        }
+                        Err(operand) => new_exp.push(operand),
+                    }
+                }
+            }
+        }
+    }
+
+    if is_commutative {
+        new_exp.sort();
+
+        new_exp = match O::duplicate_behavior() {
+            DuplicateBehavior::Merge => {
+                new_exp.dedup();
+                new_exp
+            }
+            DuplicateBehavior::Cancel => cancel_pairs(new_exp),
+        };
+
+        while let Some((i, j)) = find_complements(&new_exp, is_complement) {
+            let constant = O::complement_value();
+            match O::constant_behavior(constant) {
+                ConstantBehavior::Dominate => return Expression::Constant(constant),
+                ConstantBehavior::Negate => negate_result = !negate_result,
+                ConstantBehavior::Skip => (),
+            }
+            new_exp.remove(j);
+            new_exp.remove(i);
+        }
+
+        new_exp = absorb::<O>(new_exp);
+        if split {
+            new_exp = O::regroup_operands(new_exp);
+            new_exp.sort();
+        }
+    }
+
+    let exp = match new_exp.len() {
+        0 => return Expression::Constant(O::zero_args_behavior().negate_if(negate_result)),
+        1 => new_exp.pop().unwrap(),
+        _ => O::new(new_exp).into_expression(),
+    };
+
+    if negate_result {
+        Not::negate_simplified(exp)
+    } else {
+        exp
+    }
+}
+
+/// Drop equal neighbors two at a time, only the odd one out stays. Generic, so
+/// `ExpressionDag` cancels its node ids with the same rule.
+pub fn cancel_pairs<T: PartialEq>(sorted: Vec<T>) -> Vec<T> {
+    let mut kept: Vec<T> = vec![];
+    for item in sorted.into_iter() {
+        if kept.last() == Some(&item) {
+            kept.pop();
+        } else {
+            kept.push(item);
+        }
+    }
+    kept
+}
+
+/// Index pair of the first two complementary operands
+pub fn find_complements<T>(
+    operands: &[T],
+    is_complement: impl Fn(&T, &T) -> bool,
+) -> Option<(usize, usize)> {
+    (0..operands.len()).find_map(|i| {
+        ((i + 1)..operands.len())
+            .find(|j| is_complement(&operands[i], &operands[*j]))
+            .map(|j| (i, j))
+    })
+}
+
+fn absorb<O: VariableLengthOperator>(vecexp: Vec<Expression>) -> Vec<Expression> {
+    let Some(parts) = vecexp
+        .iter()
+        .map(O::absorption_parts)
+        .collect::<Option<Vec<Vec<Expression>>>>()
+    else {
+        return vecexp;
+    };
+    absorb_by_parts(vecexp, &parts)
+}
+
+/// Drop every operand whose parts include all parts of another one, `parts[i]` belongs to
+/// `operands[i]`
+pub fn absorb_by_parts<T, P: PartialEq>(operands: Vec<T>, parts: &[Vec<P>]) -> Vec<T> {
+    let covers = |i: usize, j: usize| parts[i].iter().all(|part| parts[j].contains(part));
+    // of two operands with the same parts, like a b and a & b, the first one stays
+    let is_absorbed =
+        |j: usize| (0..parts.len()).any(|i| i != j && covers(i, j) && (i < j || !covers(j, i)));
+    let absorbed: Vec<bool> = (0..operands.len()).map(is_absorbed).collect();
+
+    operands
+        .into_iter()
+        .zip(absorbed)
+        .filter_map(|(operand, absorbed)| (!absorbed).then_some(operand))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parser::grammar::parse, traits::Simplify};
+
+    #[test]
+    fn flattens_and_sorts() {
+        assert_eq!(
+            parse("(c | (b | 0)) | a").unwrap().simplify(),
+            parse("a | b | c").unwrap()
+        );
+        assert_eq!(
+            parse("c & ((x | b) & 1) & (a ^ 0)").unwrap().simplify(),
+            parse("a & c & (b | x)").unwrap()
+        );
+    }
+
+    #[test]
+    fn folds_constants() {
+        assert_eq!(parse("a & 0 & b").unwrap().simplify(), parse("0").unwrap());
+        assert_eq!(parse("a | 1 | b").unwrap().simplify(), parse("1").unwrap());
+        assert_eq!(
+            parse("1 ^ a ^ 1 ^ 1").unwrap().simplify(),
+            parse("~(a | 0)").unwrap().simplify()
+        );
+        assert_eq!(parse("1 ^ 1 ^ 1").unwrap().simplify(), parse("1").unwrap());
+        assert_eq!(
+            parse("~~(a | b)").unwrap().simplify(),
+            parse("a | b").unwrap()
+        );
+    }
+
+    #[test]
+    fn idempotence_and_complement() {
+        let simplified = |source: &str| parse(source).unwrap().simplify();
+
+        assert_eq!(simplified("a | a"), simplified("a"));
+        assert_eq!(simplified("(a ^ b) & (b ^ a)"), simplified("a ^ b"));
+        assert_eq!(simplified("(a | b) & ~(b | a)"), simplified("0"));
+        assert_eq!(simplified("a | c | ~a"), simplified("1"));
+        assert_eq!(
+            simplified("~~~(a b) & c & ~(a b)"),
+            simplified("~(a b) & c")
+        );
+        // literals of a term meet the other operands
+        assert_eq!(simplified("(a b) & ~a"), simplified("0"));
+        assert_eq!(simplified("(a b) & (b c) & d"), simplified("a b c d"));
+        assert_eq!(simplified("((a b) & (c | d)) & ~a"), simplified("0"));
+    }
+
+    #[test]
+    fn absorption() {
+        let simplified = |source: &str| parse(source).unwrap().simplify();
+
+        assert_eq!(simplified("a | a b"), simplified("a"));
+        assert_eq!(simplified("a b | a b c | c"), simplified("a b | c"));
+        assert_eq!(
+            simplified("(a | b) & (a | b | c) & x"),
+            simplified("(a | b) & x")
+        );
+        assert_eq!(simplified("(a ^ b) | (a ^ b) & c"), simplified("a ^ b"));
+    }
+
+    #[test]
+    fn xor_cancellation() {
+        let simplified = |source: &str| parse(source).unwrap().simplify();
+
+        assert_eq!(simplified("a ^ b ^ a"), simplified("b"));
+        assert_eq!(simplified("a ^ ~a"), simplified("1"));
+        assert_eq!(simplified("(a | b) ^ c ^ ~(a | b)"), simplified("~c"));
+        assert_eq!(simplified("(a b) ^ (b a) ^ (a b)"), simplified("a b"));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    scope::{VarPatterns, VarScope},
+    ternary::Ternary,
+    traits::{Eval, EvalBits, EvalTernary, Operator, Simplify, VarVisibility},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Not(pub Box<Expression>);
+
+impl Not {
+    /// Negate an already simplified expression without leaving a double negation
+    pub fn negate_simplified(exp: Expression) -> Expression {
+        match exp {
+            Expression::Constant(constant) => Expression::Constant(constant.negate()),
+            Expression::Var(var) => Expression::Var(var.negate()),
+            Expression::Operation(Operation::NOT(Not(inner))) => *inner,
+            other => Expression::Operation(Operation::NOT(Not(Box::new(other)))),
+        }
+    }
+}
+
+impl Operator for Not {
+    fn is_commutative(&self) -> bool {
+        // a single operand, there's nothing to reorder
+        false
+    }
+    fn is_associative(&self) -> bool {
+        false
+    }
+}
+
+impl VarVisibility for Not {
+    fn get_used_variables(&self, varset: &mut crate::interner::VarSet) {
+        self.0.get_used_variables(varset)
+    }
+}
+
+impl Eval for Not {
+    fn evaluate(&self, scope: &VarScope) -> Result<Constant, String> {
+        Ok(self.0.evaluate(scope)?.negate())
+    }
+}
+
+impl EvalBits for Not {
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String> {
+        Ok(!self.0.evaluate_bits(patterns)?)
+    }
+}
+
+impl EvalTernary for Not {
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary {
+        self.0.evaluate_ternary(scope).negate()
+    }
+}
+
+impl Simplify for Not {
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression {
+        Self::negate_simplified(self.0.simplify_pass(scope))
+    }
+}
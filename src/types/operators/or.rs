@@ -0,0 +1,125 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    scope::{VarPatterns, VarScope},
+    ternary::Ternary,
+    traits::{
+        ConstantBehavior, DuplicateBehavior, Eval, EvalBits, EvalTernary, Operator, Simplify,
+        VarVisibility, VariableLengthOperator,
+    },
+};
+
+use super::{simplify_operands, And};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Or(pub Vec<Expression>);
+
+impl Or {
+    /// The operands `exp` is the OR of
+    pub fn disjuncts(exp: &Expression) -> Vec<Expression> {
+        match exp {
+            Expression::Operation(Operation::OR(Or(vecexp))) => {
+                vecexp.iter().flat_map(Self::disjuncts).collect()
+            }
+            other => vec![other.clone()],
+        }
+    }
+}
+
+impl VariableLengthOperator for Or {
+    fn new(operands: Vec<Expression>) -> Self {
+        Or(operands)
+    }
+    fn operands(&self) -> &[Expression] {
+        &self.0
+    }
+    fn into_operands(self) -> Vec<Expression> {
+        self.0
+    }
+
+    fn into_expression(self) -> Expression {
+        Expression::Operation(Operation::OR(self))
+    }
+    fn try_from_expression(exp: Expression) -> Result<Self, Expression> {
+        match exp {
+            Expression::Operation(Operation::OR(inner)) => Ok(inner),
+            other => Err(other),
+        }
+    }
+
+    fn zero_args_behavior() -> Constant {
+        Constant::ZERO
+    }
+    fn constant_behavior(constant: Constant) -> ConstantBehavior {
+        match constant {
+            Constant::ZERO => ConstantBehavior::Skip,
+            Constant::ONE => ConstantBehavior::Dominate,
+        }
+    }
+
+    fn duplicate_behavior() -> DuplicateBehavior {
+        DuplicateBehavior::Merge
+    }
+    fn complement_value() -> Constant {
+        Constant::ONE
+    }
+    fn absorption_parts(exp: &Expression) -> Option<Vec<Expression>> {
+        Some(And::conjuncts(exp))
+    }
+}
+
+impl Operator for Or {
+    fn is_commutative(&self) -> bool {
+        true
+    }
+    fn is_associative(&self) -> bool {
+        true
+    }
+}
+
+impl VarVisibility for Or {
+    fn get_used_variables(&self, varset: &mut crate::interner::VarSet) {
+        self.0.iter().for_each(|exp| exp.get_used_variables(varset))
+    }
+}
+
+impl EvalBits for Or {
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String> {
+        self.0
+            .iter()
+            .try_fold(0, |rows, exp| Ok(rows | exp.evaluate_bits(patterns)?))
+    }
+}
+
+impl EvalTernary for Or {
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary {
+        let mut result = Ternary::ZERO;
+        for exp in self.0.iter() {
+            match exp.evaluate_ternary(scope) {
+                // a single 1 decides, even after unknown operands
+                Ternary::ONE => return Ternary::ONE,
+                value => result = result.or(value),
+            }
+        }
+        result
+    }
+}
+
+impl Simplify for Or {
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression {
+        simplify_operands(self, scope)
+    }
+}
+
+impl Eval for Or {
+    fn evaluate(&self, scope: &VarScope) -> Result<Constant, String> {
+        for exp in self.0.iter() {
+            match exp.evaluate(scope)? {
+                Constant::ONE => return Ok(Constant::ONE),
+                Constant::ZERO => continue,
+            }
+        }
+        Ok(Constant::ZERO)
+    }
+}
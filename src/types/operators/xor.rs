@@ -0,0 +1,115 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    operation::Operation,
+    scope::{VarPatterns, VarScope},
+    ternary::Ternary,
+    traits::{
+        ConstantBehavior, DuplicateBehavior, Eval, EvalBits, EvalTernary, Operator, Simplify,
+        VarVisibility, VariableLengthOperator,
+    },
+};
+
+use super::simplify_operands;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Xor(pub Vec<Expression>);
+
+impl VariableLengthOperator for Xor {
+    fn new(operands: Vec<Expression>) -> Self {
+        Xor(operands)
+    }
+    fn operands(&self) -> &[Expression] {
+        &self.0
+    }
+    fn into_operands(self) -> Vec<Expression> {
+        self.0
+    }
+
+    fn into_expression(self) -> Expression {
+        Expression::Operation(Operation::XOR(self))
+    }
+    fn try_from_expression(exp: Expression) -> Result<Self, Expression> {
+        match exp {
+            Expression::Operation(Operation::XOR(inner)) => Ok(inner),
+            other => Err(other),
+        }
+    }
+
+    fn zero_args_behavior() -> Constant {
+        Constant::ZERO
+    }
+    fn constant_behavior(constant: Constant) -> ConstantBehavior {
+        match constant {
+            Constant::ZERO => ConstantBehavior::Skip,
+            Constant::ONE => ConstantBehavior::Negate,
+        }
+    }
+
+    fn duplicate_behavior() -> DuplicateBehavior {
+        DuplicateBehavior::Cancel
+    }
+    fn complement_value() -> Constant {
+        Constant::ONE
+    }
+}
+
+impl Operator for Xor {
+    fn is_commutative(&self) -> bool {
+        true
+    }
+    fn is_associative(&self) -> bool {
+        true
+    }
+}
+
+impl VarVisibility for Xor {
+    fn get_used_variables(&self, varset: &mut crate::interner::VarSet) {
+        self.0.iter().for_each(|exp| exp.get_used_variables(varset))
+    }
+}
+
+impl EvalBits for Xor {
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String> {
+        self.0
+            .iter()
+            .try_fold(0, |rows, exp| Ok(rows ^ exp.evaluate_bits(patterns)?))
+    }
+}
+
+impl EvalTernary for Xor {
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary {
+        let mut result = Ternary::ZERO;
+        for exp in self.0.iter() {
+            match exp.evaluate_ternary(scope) {
+                // nothing later can make an unknown operand known
+                Ternary::X => return Ternary::X,
+                value => result = result.xor(value),
+            }
+        }
+        result
+    }
+}
+
+impl Simplify for Xor {
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression {
+        simplify_operands(self, scope)
+    }
+}
+
+impl Eval for Xor {
+    fn evaluate(&self, scope: &VarScope) -> Result<Constant, String> {
+        let mut ones = 0;
+        for exp in self.0.iter() {
+            if exp.evaluate(scope)? == Constant::ONE {
+                ones += 1;
+            }
+        }
+
+        if ones % 2 == 0 {
+            Ok(Constant::ZERO)
+        } else {
+            Ok(Constant::ONE)
+        }
+    }
+}
@@ -1,33 +1,103 @@
-use std::collections::HashMap;
-
 use crate::{constant::Constant, var::VarName};
 
-// pub type VarScope = HashMap<VarName, Constant>;
 pub type VarScope<'a> = VarValue<'a, Constant>;
+/// 64 rows per variable, for `EvalBits`
+pub type VarPatterns<'a> = VarValue<'a, u64>;
 
+/// Values of variables indexed by `VarName::index`. The vector only reaches the highest index
+/// assigned, however many names were interned before. Variables without a value here are
+/// looked up in `fallback`.
+#[derive(Clone, Debug)]
 pub struct VarValue<'a, T> {
-    pub mappings: HashMap<VarName, T>,
+    values: Vec<Option<T>>,
     pub fallback: Option<&'a Self>,
 }
 
+impl VarValue<'_, Constant> {
+    /// Assign the bits of `minterm` to `names`, the first name gets the most significant bit
+    pub fn new_from_minterm(names: &[VarName], minterm: u64) -> Self {
+        let mut scope = Self::new();
+        scope.assign_minterm(names, minterm);
+        scope
+    }
+
+    /// Same as `new_from_minterm` reusing this scope, for loops over many rows
+    pub fn assign_minterm(&mut self, names: &[VarName], minterm: u64) {
+        let len = names.len();
+        for (i, name) in names.iter().enumerate() {
+            self.insert(*name, Constant::from((minterm >> (len - 1 - i)) & 1 == 1));
+        }
+    }
+}
+
+impl<T> Default for VarValue<'_, T> {
+    fn default() -> Self {
+        VarValue {
+            values: vec![],
+            fallback: None,
+        }
+    }
+}
+
 impl<'a, T> VarValue<'a, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value `name` had before
+    pub fn insert(&mut self, name: VarName, value: T) -> Option<T> {
+        if name.index() >= self.values.len() {
+            self.values.resize_with(name.index() + 1, || None);
+        }
+        self.values[name.index()].replace(value)
+    }
+
     pub fn get(&self, key: &VarName) -> Option<&T> {
-        self.mappings.get(key).or_else(|| {
-            if let Some(fallback) = &self.fallback {
-                fallback.get(key)
-            } else {
-                None
-            }
-        })
-    }
-
-    pub fn get_key_value(&self, key: &VarName) -> Option<(&VarName, &T)> {
-        self.mappings.get_key_value(key).or_else(|| {
-            if let Some(fallback) = &self.fallback {
-                fallback.get_key_value(key)
-            } else {
-                None
-            }
-        })
+        self.values
+            .get(key.index())
+            .and_then(Option::as_ref)
+            .or_else(|| self.fallback.and_then(|fallback| fallback.get(key)))
+    }
+}
+
+impl<T> FromIterator<(VarName, T)> for VarValue<'_, T> {
+    fn from_iter<I: IntoIterator<Item = (VarName, T)>>(iter: I) -> Self {
+        let mut scope = Self::new();
+        for (name, value) in iter {
+            scope.insert(name, value);
+        }
+        scope
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lookup_and_fallback() {
+        let [a, b, c] = ["a", "b", "c"].map(VarName::new_from_identifier);
+        let outer: VarScope = [(a, Constant::ONE), (b, Constant::ONE)]
+            .into_iter()
+            .collect();
+        let mut inner = VarScope::new_from_minterm(&[b], 0);
+        inner.fallback = Some(&outer);
+
+        assert_eq!(inner.get(&a), Some(&Constant::ONE));
+        assert_eq!(inner.get(&b), Some(&Constant::ZERO));
+        assert_eq!(inner.get(&c), None);
+        assert_eq!(inner.insert(b, Constant::ONE), Some(Constant::ZERO));
+    }
+
+    #[test]
+    fn sized_by_its_variables() {
+        let names = VarName::numbered(300);
+        let scope = VarScope::new_from_minterm(&names[..1], 1);
+        assert_eq!(scope.values.len(), names[0].index() + 1);
+
+        let scope = VarScope::new_from_minterm(&names[299..], 1);
+        assert_eq!(scope.values.len(), names[299].index() + 1);
+        assert_eq!(scope.get(&names[299]), Some(&Constant::ONE));
+        assert_eq!(scope.get(&names[0]), None);
     }
 }
@@ -1,12 +1,16 @@
 use crate::{
     constant::Constant,
     expression::Expression,
+    format::notation::current_notation,
     operation::Operation,
-    traits::{Eval, Simplify, VarVisibility},
-    var::Var,
+    operators::{Not, Or},
+    scope::{VarPatterns, VarScope},
+    ternary::Ternary,
+    traits::{Eval, EvalBits, EvalTernary, Simplify, VarVisibility},
+    var::{Var, VarName},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Term {
     vars: Vec<Var>,
 }
@@ -16,19 +20,21 @@ impl Term {
         vars.sort();
         Term { vars }
     }
+    /// Minterm over x₁..xₙ, x₁ is the most significant bit. Looks the names up every time, loops
+    /// better get `VarName::numbered` once and use `new_from_minterm_of`.
     pub fn new_from_minterm(len: i32, minterm: u64) -> Self {
-        let mut vars = Vec::new();
-
-        for i in 0..len {
-            // Check if the i-th bit is set in the minterm
-            let bit = (minterm >> (len - 1 - i)) & 1;
-            // let name = (b'a' + i as u8) as char; // Assuming variables are named 'a', 'b', 'c', ...
-            let negated = bit == 0; // If the bit is 0, the variable is negated
-
-            vars.push(Var::new('x', Some((i + 1) as u8), negated));
-        }
+        Self::new_from_minterm_of(&VarName::numbered(len as usize), minterm)
+    }
 
-        Term { vars }
+    /// Same as `new_from_minterm`, but over the given variables instead of x₁..xₙ
+    pub fn new_from_minterm_of(names: &[VarName], minterm: u64) -> Self {
+        let len = names.len();
+        let vars = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Var::new_from_name(*name, (minterm >> (len - 1 - i)) & 1 == 0))
+            .collect();
+        Self::new_from_vars(vars)
     }
 
     pub fn new_from_neighbors(term1: &Self, term2: &Self) -> Option<Self> {
@@ -48,19 +54,57 @@ impl Term {
         Some(Self::new_from_vars(merged))
     }
 
+    /// AND of both terms, None if it contains a variable and its negation
+    pub fn conjoin(&self, other: &Self) -> Option<Self> {
+        let mut vars: Vec<Var> = self.vars.iter().chain(other.vars.iter()).copied().collect();
+        vars.sort();
+        vars.dedup();
+        if vars.windows(2).any(|pair| pair[0].is_dual(&pair[1])) {
+            return None;
+        }
+        Some(Term { vars })
+    }
+
+    /// x a | ~x b implies a b, the consensus only exists when exactly one variable is opposed
+    pub fn consensus(&self, other: &Self) -> Option<Self> {
+        let mut opposed = self.vars.iter().filter(|var| other.has_var(var.negate()));
+        let pivot = *opposed.next()?;
+        if opposed.next().is_some() {
+            return None;
+        }
+
+        let vars = self
+            .vars
+            .iter()
+            .chain(other.vars.iter())
+            .filter(|var| !var.has_same_name(&pivot))
+            .copied()
+            .collect();
+        let mut consensus = Self::new_from_vars(vars);
+        consensus.vars.dedup();
+        Some(consensus)
+    }
+
     pub fn apply_de_morgan(self) -> Expression {
         let new_vars = self
             .vars
             .into_iter()
             .map(|v| Expression::Var(v.negate()))
             .collect();
-        Expression::Operation(Operation::NOT(Box::new(Expression::Operation(
-            Operation::OR(new_vars),
-        ))))
+        Expression::Operation(Operation::NOT(Not(Box::new(Expression::Operation(
+            Operation::OR(Or(new_vars)),
+        )))))
     }
 
+    pub fn vars(&self) -> &[Var] {
+        &self.vars
+    }
     pub fn has_var(&self, var: Var) -> bool {
-        self.vars.iter().any(|e| var == *e)
+        self.vars.contains(&var)
+    }
+    /// Every assignment satisfying `other` also satisfies `self`
+    pub fn covers(&self, other: &Self) -> bool {
+        self.vars.iter().all(|var| other.has_var(*var))
     }
     pub fn is_neighbor(&self, other: &Self) -> bool {
         if self.vars.len() != other.vars.len() {
@@ -105,7 +149,7 @@ impl Term {
 }
 
 impl VarVisibility for Term {
-    fn get_used_variables(&self, varset: &mut std::collections::HashSet<crate::var::VarName>) {
+    fn get_used_variables(&self, varset: &mut crate::interner::VarSet) {
         for var in self.vars.iter() {
             var.get_used_variables(varset);
         }
@@ -122,11 +166,33 @@ impl Eval for Term {
         Ok(Constant::ONE)
     }
 }
+impl EvalBits for Term {
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String> {
+        self.vars
+            .iter()
+            .try_fold(!0, |rows, var| Ok(rows & var.evaluate_bits(patterns)?))
+    }
+}
+
+impl EvalTernary for Term {
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary {
+        let mut result = Ternary::ONE;
+        for var in self.vars.iter() {
+            match var.evaluate_ternary(scope) {
+                // a single 0 decides, even after unknown ones
+                Ternary::ZERO => return Ternary::ZERO,
+                value => result = result.and(value),
+            }
+        }
+        result
+    }
+}
+
 impl Simplify for Term {
-    fn simplify_with(self, scope: &crate::scope::VarScope) -> Expression {
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression {
         let mut required_vars: Vec<Var> = vec![];
         for var in self.vars.into_iter() {
-            match var.simplify_with(scope) {
+            match var.simplify_pass(scope) {
                 Expression::Constant(Constant::ZERO) => {
                     return Expression::Constant(Constant::ZERO)
                 },
@@ -135,49 +201,27 @@ impl Simplify for Term {
                 other => unreachable!("Variable evaluation can only return Constant or the Variable. Happened with: {:?}", other),
             };
         }
+
+        // vars are sorted, so duplicates and dual forms are next to each other
+        required_vars.dedup();
+        if required_vars
+            .windows(2)
+            .any(|pair| pair[0].is_dual(&pair[1]))
+        {
+            // x & ~x = 0
+            return Expression::Constant(Constant::ZERO);
+        }
+
         if required_vars.is_empty() {
             Expression::Constant(Constant::ONE)
         } else {
             Expression::Term(Term::new_from_vars(required_vars))
         }
     }
-    fn simplify(self) -> Expression {
-        // assumming Term is sorted, with non negated vars coming first
-        // let mut required_vars: Vec<Var> = vec![];
-
-        let mut redundant_vars = vec![false; self.vars.len()];
-
-        for i in 0..(self.vars.len()) {
-            let var1 = self.vars[i];
-
-            if i != self.vars.len() - 1 {
-                let var2 = self.vars[i + 1];
-                // check for duplicates, dual forms
-                if var1 == var2 {
-                    redundant_vars[i + 1] = true;
-                } else if var1.is_dual(&var2) {
-                    // x & ~x = 0
-                    return Expression::Constant(Constant::ZERO);
-                }
-            }
-        }
-        Expression::Term(Term::new_from_vars(
-            self.vars
-                .into_iter()
-                .enumerate()
-                .filter_map(|(i, v)| if !redundant_vars[i] { Some(v) } else { None })
-                .collect(),
-        ))
-    }
 }
 
 impl std::fmt::Display for Term {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "(")?;
-        for var in self.vars.iter() {
-            write!(f, "{}", var)?;
-        }
-        write!(f, ")")?;
-        Ok(())
+        write!(f, "{}", current_notation().term(self))
     }
 }
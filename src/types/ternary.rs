@@ -0,0 +1,152 @@
+use crate::constant::Constant;
+
+/// Kleene's three values, X is unknown: it could be either and nothing decided which yet
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Ternary {
+    ZERO,
+    ONE,
+    X,
+}
+
+impl Ternary {
+    pub fn negate(self) -> Ternary {
+        match self {
+            Self::ZERO => Ternary::ONE,
+            Self::ONE => Ternary::ZERO,
+            Self::X => Ternary::X,
+        }
+    }
+    pub fn negate_if(self, do_negate: bool) -> Ternary {
+        if do_negate {
+            self.negate()
+        } else {
+            self
+        }
+    }
+
+    /// 0 wins over X, X over 1
+    pub fn and(self, other: Ternary) -> Ternary {
+        match (self, other) {
+            (Self::ZERO, _) | (_, Self::ZERO) => Ternary::ZERO,
+            (Self::X, _) | (_, Self::X) => Ternary::X,
+            _ => Ternary::ONE,
+        }
+    }
+    /// 1 wins over X, X over 0
+    pub fn or(self, other: Ternary) -> Ternary {
+        self.negate().and(other.negate()).negate()
+    }
+    /// Unknown as soon as either is
+    pub fn xor(self, other: Ternary) -> Ternary {
+        match (self, other) {
+            (Self::X, _) | (_, Self::X) => Ternary::X,
+            _ if self == other => Ternary::ZERO,
+            _ => Ternary::ONE,
+        }
+    }
+
+    /// The value if it's known
+    pub fn to_constant(self) -> Option<Constant> {
+        match self {
+            Self::ZERO => Some(Constant::ZERO),
+            Self::ONE => Some(Constant::ONE),
+            Self::X => None,
+        }
+    }
+}
+
+impl From<Constant> for Ternary {
+    fn from(value: Constant) -> Self {
+        match value {
+            Constant::ZERO => Ternary::ZERO,
+            Constant::ONE => Ternary::ONE,
+        }
+    }
+}
+
+impl std::fmt::Display for Ternary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZERO => write!(f, "0"),
+            Self::ONE => write!(f, "1"),
+            Self::X => write!(f, "X"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parser::grammar::parse,
+        scope::VarScope,
+        testing::Generator,
+        traits::{Eval, EvalTernary, VarVisibility},
+    };
+
+    #[test]
+    fn kleene_tables() {
+        use Ternary::*;
+        let values = [ZERO, ONE, X];
+        let and = values.map(|a| values.map(|b| a.and(b)));
+        assert_eq!(and, [[ZERO, ZERO, ZERO], [ZERO, ONE, X], [ZERO, X, X]]);
+        let or = values.map(|a| values.map(|b| a.or(b)));
+        assert_eq!(or, [[ZERO, ONE, X], [ONE, ONE, ONE], [X, ONE, X]]);
+        let xor = values.map(|a| values.map(|b| a.xor(b)));
+        assert_eq!(xor, [[ZERO, ONE, X], [ONE, ZERO, X], [X, X, X]]);
+    }
+
+    #[test]
+    fn partial_assignments() {
+        let reset: VarScope = [("rst".parse().unwrap(), Constant::ONE)]
+            .into_iter()
+            .collect();
+
+        // a reset forces the output whatever the data is
+        let exp = parse("rst | d & en").unwrap();
+        assert_eq!(exp.evaluate_ternary(&reset), Ternary::ONE);
+        assert_eq!(
+            parse("~rst & d").unwrap().evaluate_ternary(&reset),
+            Ternary::ZERO
+        );
+        assert_eq!(
+            parse("rst ^ d").unwrap().evaluate_ternary(&reset),
+            Ternary::X
+        );
+        assert_eq!(parse("rst d").unwrap().evaluate_ternary(&reset), Ternary::X);
+        assert_eq!(
+            parse("~rst d").unwrap().evaluate_ternary(&reset),
+            Ternary::ZERO
+        );
+        // Kleene logic doesn't see that d | ~d is always 1
+        assert_eq!(
+            parse("d | ~d").unwrap().evaluate_ternary(&reset),
+            Ternary::X
+        );
+    }
+
+    #[test]
+    fn known_values_hold_for_every_completion() {
+        let mut generator = Generator(0x3a1);
+        for _ in 0..100 {
+            let exp = generator.expression(3);
+            let names = exp.get_sorted_variables();
+            // bind only the first half of the variables
+            let bound = names.len() / 2;
+            for partial in 0..(1u64 << bound) {
+                let scope = VarScope::new_from_minterm(&names[..bound], partial);
+                let Some(known) = exp.evaluate_ternary(&scope).to_constant() else {
+                    continue;
+                };
+                for rest in 0..(1u64 << (names.len() - bound)) {
+                    let full = VarScope::new_from_minterm(
+                        &names,
+                        (partial << (names.len() - bound)) | rest,
+                    );
+                    assert_eq!(exp.evaluate(&full), Ok(known), "{}", exp);
+                }
+            }
+        }
+    }
+}
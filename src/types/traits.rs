@@ -1,21 +1,63 @@
-use std::collections::HashSet;
-
-use crate::{constant::Constant, expression::Expression, scope::VarScope, var::VarName};
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    interner::VarSet,
+    scope::{VarPatterns, VarScope},
+    ternary::Ternary,
+    var::VarName,
+};
 
 pub trait Eval {
     /// Given values of all variables, evaluate the expression.
     fn evaluate(&self, scope: &VarScope) -> Result<Constant, String>;
 }
 
-pub trait Simplify {
+pub trait EvalBits {
+    /// Evaluate 64 assignments at once, bit i of every pattern is the variable's value in row i
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String>;
+}
+
+pub trait EvalTernary {
+    /// Evaluate with the variables `scope` doesn't have left unknown, as X
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary;
+}
+
+pub trait Simplify: Sized {
+    /// One bottom-up pass of the rules, replacing the variables `scope` has values for
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression;
+
+    /// Repeat passes until nothing changes, so simplifying the result again is a no-op
+    fn simplify_in(self, scope: Option<&VarScope>) -> Expression {
+        let mut exp = self.simplify_pass(scope);
+        loop {
+            let next = exp.clone().simplify_pass(scope);
+            if next == exp {
+                return exp;
+            }
+            exp = next;
+        }
+    }
     /// Given values of SOME variables, simplify the expression
-    fn simplify_with(self, scope: &VarScope) -> Expression;
+    fn simplify_with(self, scope: &VarScope) -> Expression {
+        self.simplify_in(Some(scope))
+    }
     /// Simplify, using univesal rules only
-    fn simplify(self) -> Expression;
+    fn simplify(self) -> Expression {
+        self.simplify_in(None)
+    }
 }
 
 pub trait VarVisibility {
-    fn get_used_variables(&self, varset: &mut HashSet<VarName>);
+    fn get_used_variables(&self, varset: &mut VarSet);
+
+    /// Used variables in their natural order, x₁ before x₂ before y
+    fn get_sorted_variables(&self) -> Vec<VarName> {
+        let mut varset = VarSet::new();
+        self.get_used_variables(&mut varset);
+        let mut names: Vec<VarName> = varset.iter().collect();
+        names.sort();
+        names
+    }
 }
 
 pub trait Operator: Eval + Simplify + VarVisibility {
@@ -27,10 +69,53 @@ pub trait Operator: Eval + Simplify + VarVisibility {
     fn is_associative(&self) -> bool;
 }
 
-// pub trait FixedLengthOperator: Operator {}
-// pub trait VariableLengthOperator: Operator {
-//     fn zero_args_behavior(self) -> Constant;
-//     fn one_arg_behavior(self, arg: Expression) -> Expression;
-//
-//     fn is_valid_argument_count(&self, arguments: usize) -> bool;
-// }
+/// How a variable length operator treats a constant operand
+pub enum ConstantBehavior {
+    /// The constant doesn't change the result, like 1 in AND
+    Skip,
+    /// The constant is the result, like 0 in AND
+    Dominate,
+    /// The constant negates the result, like 1 in XOR
+    Negate,
+}
+
+/// What a variable length operator does with an operand that appears twice
+pub enum DuplicateBehavior {
+    /// x * x = x, like in AND and OR
+    Merge,
+    /// x * x = identity, like in XOR
+    Cancel,
+}
+
+pub trait VariableLengthOperator: Operator + Sized {
+    fn new(operands: Vec<Expression>) -> Self;
+    fn operands(&self) -> &[Expression];
+    fn into_operands(self) -> Vec<Expression>;
+
+    fn into_expression(self) -> Expression;
+    /// Gives the expression back if it isn't this operator
+    fn try_from_expression(exp: Expression) -> Result<Self, Expression>;
+
+    /// Result when there are no operands
+    fn zero_args_behavior() -> Constant;
+    fn constant_behavior(constant: Constant) -> ConstantBehavior;
+
+    fn duplicate_behavior() -> DuplicateBehavior;
+    /// Value of x * ~x, it gets treated like a constant operand
+    fn complement_value() -> Constant;
+    /// The operands `exp` stands for under the dual operator, `x` absorbs `y` when all parts
+    /// of `x` are parts of `y`, like a | a b = a. None if the operator doesn't absorb.
+    fn absorption_parts(_exp: &Expression) -> Option<Vec<Expression>> {
+        None
+    }
+    /// Operands `exp` stands for, like the literals of a term under AND. They are compared
+    /// with the other operands one by one, so a b & ~a finds its complement. Gives the
+    /// expression back if it doesn't split.
+    fn split_operand(exp: Expression) -> Result<Vec<Expression>, Expression> {
+        Err(exp)
+    }
+    /// Puts what is left of split operands back together, the result gets sorted again
+    fn regroup_operands(operands: Vec<Expression>) -> Vec<Expression> {
+        operands
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{
+    constant::Constant,
+    expression::Expression,
+    scope::VarPatterns,
+    term::Term,
+    traits::{EvalBits, VarVisibility},
+    var::VarName,
+};
+
+// 2^n rows get evaluated, anything bigger than this takes way too long
+pub const MAX_VARIABLES: usize = 24;
+
+/// A Boolean function over `names`, independent of any formula. Row i is minterm i, the first
+/// name is its most significant bit, and rows are packed 64 to a word.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TruthTable {
+    names: Vec<VarName>,
+    words: Vec<u64>,
+}
+
+impl TruthTable {
+    /// Over the sorted variables of `exp`
+    pub fn new_from_expression(exp: &Expression) -> Result<Self, String> {
+        let (names, words) = truth_table_words(exp)?;
+        Ok(TruthTable { names, words })
+    }
+
+    /// Over `names`, which must include every variable of `exp` and may have more
+    pub fn new_from_expression_over(exp: &Expression, names: &[VarName]) -> Result<Self, String> {
+        Self::check_names(names)?;
+        Ok(TruthTable {
+            names: names.to_vec(),
+            words: truth_table_words_of(exp, names)?,
+        })
+    }
+
+    pub fn new_from_minterms(names: &[VarName], minterms: &[u64]) -> Result<Self, String> {
+        let mut table = Self::new_constant(names, Constant::ZERO)?;
+        for minterm in minterms {
+            if *minterm >= table.rows() {
+                return Err(format!(
+                    "Minterm {} doesn't exist with {} variables!",
+                    minterm,
+                    names.len()
+                ));
+            }
+            table.set(*minterm, Constant::ONE);
+        }
+        Ok(table)
+    }
+
+    pub fn new_constant(names: &[VarName], value: Constant) -> Result<Self, String> {
+        Self::check_names(names)?;
+        let mut table = TruthTable {
+            names: names.to_vec(),
+            words: vec![0; (1usize << names.len()).div_ceil(64)],
+        };
+        if value == Constant::ONE {
+            table.words.fill(!0);
+            table.clear_unused_bits();
+        }
+        Ok(table)
+    }
+
+    pub fn names(&self) -> &[VarName] {
+        &self.names
+    }
+    pub fn rows(&self) -> u64 {
+        1 << self.names.len()
+    }
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn get(&self, row: u64) -> Constant {
+        Constant::from((self.words[row as usize / 64] >> (row % 64)) & 1 == 1)
+    }
+    pub fn set(&mut self, row: u64, value: Constant) {
+        let bit = 1 << (row % 64);
+        match value {
+            Constant::ONE => self.words[row as usize / 64] |= bit,
+            Constant::ZERO => self.words[row as usize / 64] &= !bit,
+        }
+    }
+
+    pub fn minterms(&self) -> Vec<u64> {
+        (0..self.rows())
+            .filter(|row| self.get(*row) == Constant::ONE)
+            .collect()
+    }
+    pub fn count_ones(&self) -> u64 {
+        self.words.iter().map(|word| word.count_ones() as u64).sum()
+    }
+
+    /// Sum of the minterms, the canonical DNF
+    pub fn to_expression(&self) -> Expression {
+        Expression::new_from_terms(
+            self.minterms()
+                .iter()
+                .map(|minterm| Term::new_from_minterm_of(&self.names, *minterm))
+                .collect(),
+        )
+    }
+
+    pub fn not(&self) -> Self {
+        let mut table = TruthTable {
+            names: self.names.clone(),
+            words: self.words.iter().map(|word| !word).collect(),
+        };
+        table.clear_unused_bits();
+        table
+    }
+    /// The result is over the variables of both tables
+    pub fn and(&self, other: &Self) -> Result<Self, String> {
+        self.combine(other, |a, b| a & b)
+    }
+    pub fn or(&self, other: &Self) -> Result<Self, String> {
+        self.combine(other, |a, b| a | b)
+    }
+    pub fn xor(&self, other: &Self) -> Result<Self, String> {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    /// `name` fixed to `value`, over the remaining variables
+    pub fn cofactor(&self, name: &VarName, value: Constant) -> Result<Self, String> {
+        let position = self
+            .names
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| format!("'{}' isn't a variable of the table!", name.identifier()))?;
+        let bit = self.names.len() - 1 - position;
+
+        let mut names = self.names.clone();
+        names.remove(position);
+        let mut table = Self::new_constant(&names, Constant::ZERO)?;
+        for row in 0..table.rows() {
+            // put the fixed bit back between the higher and lower ones
+            let high = (row >> bit) << (bit + 1);
+            let low = row & ((1 << bit) - 1);
+            let fixed = u64::from(bool::from(value)) << bit;
+            table.set(row, self.get(high | fixed | low));
+        }
+        Ok(table)
+    }
+
+    /// Same function over `names`, which must include every variable of the table
+    pub fn extend_to(&self, names: &[VarName]) -> Result<Self, String> {
+        if let Some(missing) = self.names.iter().find(|name| !names.contains(name)) {
+            return Err(format!(
+                "'{}' is missing from the new variables!",
+                missing.identifier()
+            ));
+        }
+        let positions: Vec<usize> = self
+            .names
+            .iter()
+            .map(|name| names.iter().position(|n| n == name).unwrap())
+            .collect();
+
+        let mut table = Self::new_constant(names, Constant::ZERO)?;
+        for row in 0..table.rows() {
+            let own_row = positions.iter().fold(0, |own_row, position| {
+                (own_row << 1) | ((row >> (names.len() - 1 - position)) & 1)
+            });
+            table.set(row, self.get(own_row));
+        }
+        Ok(table)
+    }
+
+    /// Both describe the same function, even over different variables
+    pub fn is_equivalent(&self, other: &Self) -> Result<bool, String> {
+        Ok(self.xor(other)?.count_ones() == 0)
+    }
+    /// Every row where `self` is 1, `other` is 1 as well
+    pub fn implies(&self, other: &Self) -> Result<bool, String> {
+        Ok(self.and(&other.not())?.count_ones() == 0)
+    }
+    pub fn is_tautology(&self) -> bool {
+        self.count_ones() == self.rows()
+    }
+    pub fn is_contradiction(&self) -> bool {
+        self.count_ones() == 0
+    }
+
+    fn combine(&self, other: &Self, op: fn(u64, u64) -> u64) -> Result<Self, String> {
+        let (left, right) = if self.names == other.names {
+            (self.clone(), other.clone())
+        } else {
+            let mut names: Vec<VarName> = self.names.clone();
+            names.extend(other.names.iter().filter(|name| !self.names.contains(name)));
+            names.sort();
+            (self.extend_to(&names)?, other.extend_to(&names)?)
+        };
+
+        let words = left
+            .words
+            .iter()
+            .zip(right.words.iter())
+            .map(|(a, b)| op(*a, *b))
+            .collect();
+        let mut table = TruthTable {
+            names: left.names,
+            words,
+        };
+        table.clear_unused_bits();
+        Ok(table)
+    }
+
+    fn clear_unused_bits(&mut self) {
+        if self.rows() < 64 {
+            self.words[0] &= (1 << self.rows()) - 1;
+        }
+    }
+
+    fn check_names(names: &[VarName]) -> Result<(), String> {
+        if names.len() > MAX_VARIABLES {
+            return Err(format!(
+                "Table has {} variables, at most {} are supported!",
+                names.len(),
+                MAX_VARIABLES
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Values of minterm bit `bit` in the 64 rows starting at `first_row`, which is a multiple of 64
+pub fn row_pattern(bit: usize, first_row: u64) -> u64 {
+    const LOW_BITS: [u64; 6] = [
+        0xAAAA_AAAA_AAAA_AAAA,
+        0xCCCC_CCCC_CCCC_CCCC,
+        0xF0F0_F0F0_F0F0_F0F0,
+        0xFF00_FF00_FF00_FF00,
+        0xFFFF_0000_FFFF_0000,
+        0xFFFF_FFFF_0000_0000,
+    ];
+    match LOW_BITS.get(bit) {
+        Some(pattern) => *pattern,
+        None if (first_row >> bit) & 1 == 1 => !0,
+        None => 0,
+    }
+}
+
+/// Truth table of `exp` over its sorted variables, row i in bit i % 64 of word i / 64. Rows
+/// are minterms, so the first name is the most significant bit.
+pub fn truth_table_words(exp: &Expression) -> Result<(Vec<VarName>, Vec<u64>), String> {
+    let names = exp.get_sorted_variables();
+    let words = truth_table_words_of(exp, &names)?;
+    Ok((names, words))
+}
+
+/// Same as `truth_table_words` over the given variables, which may include unused ones
+pub fn truth_table_words_of(exp: &Expression, names: &[VarName]) -> Result<Vec<u64>, String> {
+    if names.len() > MAX_VARIABLES {
+        return Err(format!(
+            "Expression has {} variables, at most {} are supported!",
+            names.len(),
+            MAX_VARIABLES
+        ));
+    }
+
+    let rows = 1u64 << names.len();
+    let mut patterns = VarPatterns::new();
+    let mut words = vec![];
+    for first_row in (0..rows).step_by(64) {
+        for (i, name) in names.iter().enumerate() {
+            patterns.insert(*name, row_pattern(names.len() - 1 - i, first_row));
+        }
+        words.push(exp.evaluate_bits(&patterns)?);
+    }
+
+    // fewer than 64 rows only use the low bits
+    if rows < 64 {
+        words[0] &= (1 << rows) - 1;
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::grammar::parse, scope::VarScope, testing::Generator, traits::Eval};
+
+    fn table(source: &str) -> TruthTable {
+        TruthTable::new_from_expression(&parse(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn minterms_and_expressions() {
+        let t = table("a ~b | c");
+        assert_eq!(t.minterms(), [1, 3, 4, 5, 7]);
+        assert_eq!(
+            TruthTable::new_from_minterms(t.names(), &[1, 3, 4, 5, 7]).unwrap(),
+            t
+        );
+        assert!(TruthTable::new_from_minterms(t.names(), &[8]).is_err());
+        assert_eq!(
+            TruthTable::new_from_expression(&t.to_expression()).unwrap(),
+            t
+        );
+    }
+
+    #[test]
+    fn operations() {
+        let a = table("a");
+        let b = table("b");
+        assert_eq!(a.and(&b).unwrap(), table("a b"));
+        assert_eq!(a.or(&b).unwrap(), table("a | b"));
+        assert_eq!(a.xor(&b).unwrap(), table("a ^ b"));
+        assert_eq!(a.not(), table("~a"));
+        assert!(a.or(&a.not()).unwrap().is_tautology());
+        assert!(a.and(&a.not()).unwrap().is_contradiction());
+    }
+
+    #[test]
+    fn cofactors() {
+        let t = table("a b | ~a c");
+        let b = "b".parse().unwrap();
+        let a = "a".parse().unwrap();
+        assert_eq!(t.cofactor(&a, Constant::ONE).unwrap(), table("b | c ~c"));
+        assert!(t
+            .cofactor(&a, Constant::ZERO)
+            .unwrap()
+            .is_equivalent(&table("c"))
+            .unwrap());
+        assert!(table("a").cofactor(&b, Constant::ONE).is_err());
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(table("a & (b | c)")
+            .is_equivalent(&table("a b | a c"))
+            .unwrap());
+        assert!(table("a b").implies(&table("a | c")).unwrap());
+        assert!(!table("a | c").implies(&table("a b")).unwrap());
+        // the same function over more variables isn't equal, but equivalent
+        assert_ne!(table("a | b ~b"), table("a"));
+        assert!(table("a | b ~b").is_equivalent(&table("a")).unwrap());
+    }
+
+    #[test]
+    fn many_variables() {
+        let mut generator = Generator(0x7ab);
+        for _ in 0..50 {
+            let exp = generator.expression(3);
+            let names = exp.get_sorted_variables();
+            let t = TruthTable::new_from_expression(&exp).unwrap();
+            let wider: Vec<VarName> = names
+                .iter()
+                .copied()
+                .chain((1..8).map(|i| VarName::new('w', Some(i))))
+                .collect();
+            let extended = t.extend_to(&wider).unwrap();
+            assert_eq!(
+                extended,
+                TruthTable::new_from_expression_over(&exp, &wider).unwrap()
+            );
+            assert_eq!(extended.count_ones(), t.count_ones() << 7);
+        }
+    }
+
+    #[test]
+    fn matches_row_by_row() {
+        let mut generator = Generator(0xb175);
+        for _ in 0..100 {
+            let exp = generator.expression(3);
+            let (names, words) = truth_table_words(&exp).unwrap();
+            for minterm in 0..(1u64 << names.len()) {
+                let bit = (words[minterm as usize / 64] >> (minterm % 64)) & 1 == 1;
+                let row = exp.evaluate(&VarScope::new_from_minterm(&names, minterm));
+                assert_eq!(row.map(bool::from), Ok(bit), "{}", exp);
+            }
+        }
+    }
+
+    #[test]
+    fn many_rows() {
+        // 8 variables, the two high bits come from the word index
+        let exp = parse("a ^ b ^ c ^ d ^ e ^ f ^ g ^ h").unwrap();
+        let (_, words) = truth_table_words(&exp).unwrap();
+        assert_eq!(words.len(), 4);
+        assert_eq!(
+            words,
+            [
+                0x6996_9669_9669_6996,
+                !0x6996_9669_9669_6996,
+                !0x6996_9669_9669_6996,
+                0x6996_9669_9669_6996
+            ]
+        );
+
+        let (_, words) = truth_table_words(&parse("a | ~b").unwrap()).unwrap();
+        assert_eq!(words, [0b1101]);
+    }
+}
@@ -1,13 +1,18 @@
 use crate::{
     expression::Expression,
-    scope::VarScope,
-    traits::{Eval, Simplify, VarVisibility},
+    format::notation::current_notation,
+    interner::{VarInterner, VarSet},
+    scope::{VarPatterns, VarScope},
+    ternary::Ternary,
+    traits::{Eval, EvalBits, EvalTernary, Simplify, VarVisibility},
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// An identifier like `a`, `x12`, `rst_n` or a quoted `"reset n"`, interned so it stays `Copy`
+/// and compares by its dense index
+#[derive(Debug, Clone, Copy)]
 pub struct VarName {
-    name: char,
-    subscript: Option<u8>,
+    identifier: &'static str,
+    index: usize,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -18,23 +23,167 @@ pub struct Var {
 
 impl VarName {
     pub fn new(name: char, subscript: Option<u8>) -> Self {
-        VarName { name, subscript }
+        match subscript {
+            None => Self::new_from_identifier(&name.to_string()),
+            Some(subscript) => Self::new_from_identifier(&format!("{}{}", name, subscript)),
+        }
     }
-    pub fn get_string(&self, negated: bool) -> String {
-        let mut out = if negated {
-            // adds bar over character, to indicate negation
-            let letter_modifier = '\u{0304}';
-            format!("{}{}", self.name, letter_modifier)
+    /// Any text is a name, the ones that aren't plain identifiers get quoted when printed
+    pub fn new_from_identifier(identifier: &str) -> Self {
+        let (index, identifier) = VarInterner::global().intern(identifier);
+        VarName { identifier, index }
+    }
+    /// x₁..xₙ, the names of inputs without a label. Cached, so fine to call in a loop.
+    pub fn numbered(count: usize) -> Vec<VarName> {
+        let mut interner = VarInterner::global();
+        let indices = interner.numbered(count).to_vec();
+        indices
+            .into_iter()
+            .map(|index| VarName::new_from_index(index, interner.identifier(index).unwrap()))
+            .collect()
+    }
+    /// For the pairs `VarInterner` hands out
+    pub(crate) fn new_from_index(index: usize, identifier: &'static str) -> Self {
+        VarName { identifier, index }
+    }
+    pub fn identifier(&self) -> &'static str {
+        self.identifier
+    }
+    /// Position in the global `VarInterner`, dense over every name created so far
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// The letter of a `letter+digits` name like x12, otherwise the whole identifier
+    pub fn name(&self) -> &'static str {
+        match self.subscript() {
+            Some(subscript) => &self.identifier[..self.identifier.len() - subscript.len()],
+            None => self.identifier,
+        }
+    }
+    /// The digits of a `letter+digits` name, which get written as a subscript
+    pub fn subscript(&self) -> Option<&'static str> {
+        let mut chars = self.identifier.chars();
+        let letter = chars.next().filter(|c| c.is_alphabetic())?;
+        let digits = &self.identifier[letter.len_utf8()..];
+        (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())).then_some(digits)
+    }
+    /// Letters, digits and underscores, not starting with a digit
+    pub fn is_plain(&self) -> bool {
+        is_identifier(self.identifier)
+    }
+    /// Plain ASCII form the parser reads back, x₁ -> x1 and quotes around anything unusual.
+    /// Quotes and backslashes inside get a backslash before them.
+    pub fn get_identifier(&self) -> String {
+        if self.is_plain() {
+            self.identifier.to_string()
         } else {
-            format!("{}", self.name)
+            let escaped = self.identifier.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("\"{}\"", escaped)
+        }
+    }
+    pub fn get_string(&self, negated: bool) -> String {
+        let Some(subscript) = self.subscript() else {
+            return match (negated, self.identifier.chars().count()) {
+                // adds bar over character, to indicate negation
+                (true, 1) => format!("{}\u{0304}", self.identifier),
+                (true, _) => format!("¬{}", self.get_identifier()),
+                (false, _) => self.get_identifier(),
+            };
         };
 
-        if let Some(subscript) = self.subscript {
-            out += decimal_to_subscript(subscript as u32).as_str();
+        let mut out = self.name().to_string();
+        if negated {
+            out.push('\u{0304}');
+        }
+        out + subscript
+            .chars()
+            .map(digit_to_subscript)
+            .collect::<String>()
+            .as_str()
+    }
+
+    /// Trailing digits compare as numbers, so x2 comes before x10
+    fn sort_key(&self) -> (&str, usize, &str) {
+        let base = self
+            .identifier
+            .trim_end_matches(|c: char| c.is_ascii_digit());
+        let number = self.identifier[base.len()..].trim_start_matches('0');
+        (base, number.len(), number)
+    }
+}
+
+impl PartialEq for VarName {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl Eq for VarName {}
+
+impl std::hash::Hash for VarName {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl Ord for VarName {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sort_key()
+            .cmp(&other.sort_key())
+            .then_with(|| self.identifier.cmp(other.identifier))
+    }
+}
+
+impl PartialOrd for VarName {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::str::FromStr for VarName {
+    type Err = String;
+
+    /// Parses a plain identifier like `x12` or `rst_n`, or a quoted name like `"reset n"` where
+    /// `\"` and `\\` stand for a quote and a backslash
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(quoted) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            return match unescape(quoted) {
+                Some(name) if !name.is_empty() => Ok(VarName::new_from_identifier(&name)),
+                _ => Err(format!("'{}' isn't a valid quoted name!", s)),
+            };
         }
+        if !is_identifier(s) {
+            return Err(format!(
+                "'{}' isn't made of letters, digits and underscores, not starting with a digit!",
+                s
+            ));
+        }
+        Ok(VarName::new_from_identifier(s))
+    }
+}
 
-        out
+/// The inside of a quoted name, None if it has a bare quote or an unknown escape
+fn unescape(quoted: &str) -> Option<String> {
+    let mut name = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('"' | '\\')) => name.push(escaped),
+                _ => return None,
+            },
+            '"' => return None,
+            c => name.push(c),
+        }
     }
+    Some(name)
+}
+
+/// Letters, digits and underscores, not starting with a digit
+pub fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
 }
 
 impl Var {
@@ -44,6 +193,9 @@ impl Var {
             negated,
         }
     }
+    pub fn new_from_name(name: VarName, negated: bool) -> Self {
+        Var { name, negated }
+    }
     pub fn is_dual(&self, other: &Self) -> bool {
         self.negated != other.negated && self.has_same_name(other)
     }
@@ -56,6 +208,12 @@ impl Var {
             negated: !self.negated,
         }
     }
+    pub fn name(&self) -> VarName {
+        self.name
+    }
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
 }
 
 impl Eval for Var {
@@ -65,36 +223,55 @@ impl Eval for Var {
                 "Variable '{}' isn't defined in this scope!",
                 self.name.get_string(false)
             )),
-            Some(constant) => Ok(*constant),
+            Some(constant) => Ok(constant.negate_if(self.negated)),
         }
     }
 }
 
 impl VarVisibility for Var {
-    fn get_used_variables(&self, varset: &mut std::collections::HashSet<VarName>) {
+    fn get_used_variables(&self, varset: &mut VarSet) {
         varset.insert(self.name);
     }
 }
 
-impl Simplify for Var {
-    fn simplify(self) -> Expression {
-        Expression::Var(self)
+impl EvalBits for Var {
+    fn evaluate_bits(&self, patterns: &VarPatterns) -> Result<u64, String> {
+        match patterns.get(&self.name) {
+            None => Err(format!(
+                "Variable '{}' isn't defined in this scope!",
+                self.name.get_string(false)
+            )),
+            Some(pattern) if self.negated => Ok(!pattern),
+            Some(pattern) => Ok(*pattern),
+        }
     }
-    fn simplify_with(self, scope: &VarScope) -> Expression {
+}
+
+impl EvalTernary for Var {
+    fn evaluate_ternary(&self, scope: &VarScope) -> Ternary {
         match scope.get(&self.name) {
+            None => Ternary::X,
+            Some(constant) => Ternary::from(constant.negate_if(self.negated)),
+        }
+    }
+}
+
+impl Simplify for Var {
+    fn simplify_pass(self, scope: Option<&VarScope>) -> Expression {
+        match scope.and_then(|scope| scope.get(&self.name)) {
             None => Expression::Var(self),
-            Some(value) => Expression::Constant(*value),
+            Some(value) => Expression::Constant(value.negate_if(self.negated)),
         }
     }
 }
 
 impl std::fmt::Display for Var {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.name.get_string(self.negated))
+        write!(f, "{}", current_notation().var(self))
     }
 }
 
-fn decimal_to_subscript(num: u32) -> String {
+fn digit_to_subscript(digit: char) -> char {
     let subscripts = [
         '\u{2080}', // Subscript 0
         '\u{2081}', // Subscript 1
@@ -108,16 +285,7 @@ fn decimal_to_subscript(num: u32) -> String {
         '\u{2089}', // Subscript 9
     ];
 
-    let mut result = String::new();
-    let digits = num.to_string();
-
-    for digit in digits.chars() {
-        if let Some(d) = digit.to_digit(10) {
-            result.push(subscripts[d as usize]);
-        }
-    }
-
-    result
+    digit.to_digit(10).map_or(digit, |d| subscripts[d as usize])
 }
 
 #[cfg(test)]
@@ -131,10 +299,7 @@ mod test {
         assert_eq!(
             zneg,
             Var {
-                name: VarName {
-                    name: 'z',
-                    subscript: None
-                },
+                name: VarName::new_from_identifier("z"),
                 negated: true
             }
         );
@@ -152,6 +317,53 @@ mod test {
         assert_eq!(q240neg.to_string(), "q̄₂₄₀");
     }
 
+    #[test]
+    fn negated_evaluation() {
+        let scope: crate::scope::VarScope =
+            [(VarName::new('x', Some(1)), crate::constant::Constant::ONE)]
+                .into_iter()
+                .collect();
+
+        let x1neg = Var::new('x', Some(1), true);
+        assert_eq!(x1neg.evaluate(&scope), Ok(crate::constant::Constant::ZERO));
+        assert!(matches!(
+            x1neg.simplify_with(&scope),
+            Expression::Constant(crate::constant::Constant::ZERO)
+        ));
+        assert_eq!("x12".parse(), Ok(VarName::new('x', Some(12))));
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!("rst_n".parse::<VarName>().unwrap().identifier(), "rst_n");
+        assert_eq!("x1a".parse::<VarName>().unwrap().subscript(), None);
+        assert_eq!("x300".parse::<VarName>().unwrap().subscript(), Some("300"));
+        assert_eq!(
+            "\"reset n\"".parse::<VarName>().unwrap(),
+            VarName::new_from_identifier("reset n")
+        );
+        assert!("_reset".parse::<VarName>().unwrap().is_plain());
+        assert!("1a".parse::<VarName>().is_err());
+        assert!("a-b".parse::<VarName>().is_err());
+        assert!("\"\"".parse::<VarName>().is_err());
+
+        let enable = VarName::new_from_identifier("enable");
+        assert_eq!(enable.get_string(true), "¬enable");
+        assert_eq!(
+            VarName::new_from_identifier("reset n").get_identifier(),
+            "\"reset n\""
+        );
+        let weird = VarName::new_from_identifier("we\"ird\\");
+        assert_eq!(weird.get_identifier(), "\"we\\\"ird\\\\\"");
+        assert_eq!(weird.get_identifier().parse(), Ok(weird));
+        assert!("\"a\"b\"".parse::<VarName>().is_err());
+        assert!("\"a\\b\"".parse::<VarName>().is_err());
+        assert_eq!(
+            Var::new_from_name("x300".parse().unwrap(), true).to_string(),
+            "x̄₃₀₀"
+        );
+    }
+
     #[test]
     fn sorting() {
         let x3neg = Var::new('x', Some(3), true);
@@ -166,5 +378,12 @@ mod test {
         let mut vec = vec![y0, x3, y0neg, y, x3neg, q240neg];
         vec.sort();
         assert_eq!(vec, [q240neg, x3, x3neg, y, y0, y0neg]);
+
+        let mut names: Vec<VarName> = ["x10", "x2", "in", "x", "in_2"]
+            .map(VarName::new_from_identifier)
+            .to_vec();
+        names.sort();
+        let identifiers: Vec<&str> = names.iter().map(VarName::identifier).collect();
+        assert_eq!(identifiers, ["in", "in_2", "x", "x2", "x10"]);
     }
 }